@@ -0,0 +1,421 @@
+use std::{
+    error::Error,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Parser, Subcommand};
+use ga::{
+    checkpoint::{read_metadata, CheckpointFormat},
+    experiment::{ExperimentConfig, ExperimentReport, LoggingConfig, Registry},
+    objective::Objective,
+    population::{MutationConfig, PopulationConfig},
+    selection::SelectionMethod,
+    stats::History,
+    termination::Termination,
+};
+
+/// Runs genetic algorithm experiments described in TOML, YAML or JSON files.
+#[derive(Debug, Parser)]
+#[command(name = "ga", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    /// Prints reports as JSON, one per line.
+    #[arg(long, global = true)]
+    json: bool,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Lists the built-in problems.
+    List,
+    /// Runs every experiment in a config file.
+    Run {
+        config: PathBuf,
+        /// Only runs the experiment with this name.
+        #[arg(long)]
+        experiment: Option<String>,
+    },
+    /// Runs a built-in problem with default parameters.
+    Bench {
+        problem: String,
+        #[arg(long, default_value_t = 100)]
+        generations: i64,
+        #[arg(long, default_value_t = 50)]
+        pop_size: usize,
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+    /// Continues a run from a checkpoint saved by an experiment.
+    Resume {
+        checkpoint: PathBuf,
+        /// Stops once the population has this many generations in total.
+        #[arg(long, required_unless_present = "evaluations")]
+        generations: Option<i64>,
+        /// Stops once this many fitness evaluations have been made in total.
+        #[arg(long)]
+        evaluations: Option<u64>,
+        /// Saves the resumed population here when it stops.
+        #[arg(long)]
+        save: Option<PathBuf>,
+    },
+    /// Summarises a history saved with `history_json`.
+    Stats {
+        history: PathBuf,
+        /// Prints every this many generations; by default about 20 rows.
+        #[arg(long)]
+        every: Option<usize>,
+    },
+    /// Runs the experiments in a config file again with another seed, printing
+    /// every generation. History and checkpoint files are not written.
+    Replay {
+        config: PathBuf,
+        #[arg(long)]
+        seed: u64,
+        #[arg(long)]
+        experiment: Option<String>,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli, &mut io::stdout().lock()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Carries out `cli`, writing reports and summaries to `out`.
+fn run(cli: Cli, out: &mut impl Write) -> Result<(), Box<dyn Error>> {
+    let registry = Registry::default();
+    let experiments = match cli.command {
+        Command::List => {
+            for name in registry.names() {
+                writeln!(out, "{}", name)?;
+            }
+            return Ok(());
+        }
+        Command::Stats { history, every } => return print_stats(&history, every, out),
+        Command::Run { config, experiment } => load(&config, experiment.as_deref())?,
+        Command::Replay {
+            config,
+            seed,
+            experiment,
+        } => load(&config, experiment.as_deref())?
+            .into_iter()
+            .map(|e| ExperimentConfig {
+                seed: Some(seed),
+                logging: LoggingConfig {
+                    every: 1,
+                    ..Default::default()
+                },
+                ..e
+            })
+            .collect(),
+        Command::Bench {
+            problem,
+            generations,
+            pop_size,
+            seed,
+        } => {
+            let objective = registry.objective(&problem).unwrap_or_default();
+            vec![bench(problem, objective, generations, pop_size, seed)]
+        }
+        Command::Resume {
+            checkpoint,
+            generations,
+            evaluations,
+            save,
+        } => {
+            let metadata = read_metadata(&checkpoint, CheckpointFormat::from_path(&checkpoint))?;
+            let problem = metadata
+                .get("problem")
+                .ok_or("the checkpoint does not name its problem")?;
+            let termination = match (generations, evaluations) {
+                (Some(g), Some(e)) => {
+                    Termination::MaxGenerations(g).or(Termination::MaxEvaluations(e))
+                }
+                (Some(g), None) => Termination::MaxGenerations(g),
+                (None, Some(e)) => Termination::MaxEvaluations(e),
+                (None, None) => unreachable!("clap requires one of them"),
+            };
+            vec![ExperimentConfig {
+                name: metadata.get("experiment").cloned().unwrap_or_default(),
+                problem: problem.clone(),
+                seed: None,
+                population: PopulationConfig::default(),
+                termination,
+                logging: LoggingConfig {
+                    checkpoint: save,
+                    ..Default::default()
+                },
+                resume: Some(checkpoint),
+            }]
+        }
+    };
+
+    for experiment in &experiments {
+        let report = registry.run(experiment)?;
+        print_report(&report, cli.json, out)?;
+    }
+    Ok(())
+}
+
+fn load(path: &Path, name: Option<&str>) -> Result<Vec<ExperimentConfig>, Box<dyn Error>> {
+    let experiments: Vec<ExperimentConfig> = ExperimentConfig::load_all(path)?
+        .into_iter()
+        .filter(|e| name.is_none_or(|n| e.name == n))
+        .collect();
+    if experiments.is_empty() {
+        return Err(format!("no matching experiments in {}", path.display()).into());
+    }
+    Ok(experiments)
+}
+
+fn bench(
+    problem: String,
+    objective: Objective,
+    generations: i64,
+    pop_size: usize,
+    seed: u64,
+) -> ExperimentConfig {
+    let offspring = pop_size * 2 / 5;
+    ExperimentConfig {
+        name: problem.clone(),
+        problem,
+        seed: Some(seed),
+        population: PopulationConfig {
+            pop_size,
+            crossover_count: offspring,
+            mutate_count: offspring,
+            elitism_count: pop_size.min(2),
+            mutation_config: MutationConfig {
+                gene_mutation_chance: 0.05,
+                ..Default::default()
+            },
+            selection: SelectionMethod::Tournament { size: 3 },
+            objective,
+            ..Default::default()
+        },
+        termination: Termination::MaxGenerations(generations),
+        logging: LoggingConfig::default(),
+        resume: None,
+    }
+}
+
+fn print_report(
+    report: &ExperimentReport,
+    json: bool,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    if json {
+        writeln!(out, "{}", serde_json::to_string(report)?)?;
+    } else {
+        let name = if report.name.is_empty() {
+            &report.problem
+        } else {
+            &report.name
+        };
+        writeln!(
+            out,
+            "{}: stopped by {:?} after {} generations and {} evaluations, best fitness {}",
+            name,
+            report.reason,
+            report.generations,
+            report.evaluations,
+            report
+                .best_fitness
+                .map(|f| f.to_string())
+                .unwrap_or_else(|| "none".to_string())
+        )?;
+    }
+    Ok(())
+}
+
+fn print_stats(
+    path: &Path,
+    every: Option<usize>,
+    out: &mut impl Write,
+) -> Result<(), Box<dyn Error>> {
+    let history = History::from_json(&fs::read_to_string(path)?)?;
+    let generations = &history.generations;
+    let Some(last) = generations.last() else {
+        writeln!(out, "{} has no generations", path.display())?;
+        return Ok(());
+    };
+
+    let every = every.unwrap_or(generations.len().div_ceil(20)).max(1);
+    let cell = |v: Option<f64>| v.map(|v| format!("{:.6}", v)).unwrap_or_default();
+    writeln!(
+        out,
+        "{:>10} {:>14} {:>14} {:>14} {:>12}",
+        "generation", "best", "mean", "std_dev", "evaluations"
+    )?;
+    for (i, s) in generations.iter().enumerate() {
+        if i % every == 0 || i + 1 == generations.len() {
+            writeln!(
+                out,
+                "{:>10} {:>14} {:>14} {:>14} {:>12}",
+                s.generation,
+                cell(s.best),
+                cell(s.mean),
+                cell(s.std_dev),
+                s.total_evaluations
+            )?;
+        }
+    }
+    let duration: f64 = generations.iter().map(|s| s.duration.as_secs_f64()).sum();
+    writeln!(
+        out,
+        "{} generations, {} evaluations, {:.3}s, final best {}",
+        generations.len(),
+        last.total_evaluations,
+        duration,
+        cell(last.best)
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use clap::{CommandFactory, Parser};
+    use ga::{experiment::ExperimentReport, stats::History};
+
+    use super::{run, Cli};
+
+    /// A directory in the temp directory, unique to the test process and
+    /// `name`, that is removed when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> TempDir {
+            let dir =
+                std::env::temp_dir().join(format!("ga_cli_test_{}_{}", std::process::id(), name));
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+
+        fn file(&self, name: &str) -> String {
+            self.0.join(name).to_str().unwrap().to_string()
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn ga(args: &[&str]) -> String {
+        let cli = Cli::try_parse_from(std::iter::once("ga").chain(args.iter().copied())).unwrap();
+        let mut out = Vec::new();
+        run(cli, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    /// Writes a small OneMax experiment that saves a checkpoint and history.
+    fn experiment(dir: &TempDir) -> String {
+        let config = dir.file("onemax.json");
+        let contents = serde_json::json!({
+            "name": "small",
+            "problem": "onemax",
+            "seed": 3,
+            "termination": { "MaxGenerations": 5 },
+            "population": {
+                "pop_size": 12,
+                "crossover_count": 4,
+                "mutate_count": 4,
+                "elitism_count": 2
+            },
+            "logging": {
+                "checkpoint": dir.file("onemax_checkpoint.json"),
+                "history_json": dir.file("onemax_history.json")
+            }
+        });
+        fs::write(&config, contents.to_string()).unwrap();
+        config
+    }
+
+    #[test]
+    fn test_cli() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_resume() {
+        let dir = TempDir::new("resume");
+        let first: ExperimentReport =
+            serde_json::from_str(&ga(&["run", &experiment(&dir), "--json"])).unwrap();
+        assert_eq!(5, first.generations);
+
+        let checkpoint = dir.file("onemax_checkpoint.json");
+        let saved = dir.file("resumed.json");
+        let resumed: ExperimentReport = serde_json::from_str(&ga(&[
+            "resume",
+            &checkpoint,
+            "--generations",
+            "9",
+            "--save",
+            &saved,
+            "--json",
+        ]))
+        .unwrap();
+        assert_eq!(
+            ("small", "onemax"),
+            (resumed.name.as_str(), resumed.problem.as_str())
+        );
+        assert_eq!(9, resumed.generations);
+        assert!(resumed.evaluations > first.evaluations);
+        assert!(resumed.best_fitness >= first.best_fitness);
+
+        let again = ga(&["resume", &saved, "--generations", "9"]);
+        assert!(
+            again.starts_with("small: stopped by MaxGenerations(9) after 9 generations"),
+            "{}",
+            again
+        );
+    }
+
+    #[test]
+    fn test_stats() {
+        let dir = TempDir::new("stats");
+        ga(&["run", &experiment(&dir)]);
+        let path = dir.file("onemax_history.json");
+        let history = History::from_json(&fs::read_to_string(&path).unwrap()).unwrap();
+        let last = history.generations.last().unwrap();
+
+        let output = ga(&["stats", &path, "--every", "2"]);
+        let lines: Vec<&str> = output.lines().collect();
+        let columns = |line: &str| {
+            line.split_whitespace()
+                .map(String::from)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            vec!["generation", "best", "mean", "std_dev", "evaluations"],
+            columns(lines[0])
+        );
+        // Every second of the five generations, then the summary.
+        assert_eq!(5, history.generations.len());
+        assert_eq!(5, lines.len());
+        let rows: Vec<Vec<String>> = lines[1..4].iter().map(|l| columns(l)).collect();
+        for (row, stats) in rows.iter().zip(history.generations.iter().step_by(2)) {
+            assert_eq!(stats.generation.to_string(), row[0]);
+            assert_eq!(format!("{:.6}", stats.best.unwrap()), row[1]);
+            assert_eq!(stats.total_evaluations.to_string(), row[4]);
+        }
+        assert!(lines[4].starts_with(&format!(
+            "5 generations, {} evaluations, ",
+            last.total_evaluations
+        )));
+        assert!(lines[4].ends_with(&format!("final best {:.6}", last.best.unwrap())));
+    }
+}
@@ -1,6 +1,7 @@
 use ga::{
     item_array::ItemArray,
-    population::{MutationConfig, Population, PopulationConfig},
+    population::{CrossoverConfig, MutationConfig, Population, PopulationConfig},
+    termination::Termination,
     traits::{Crossover, Fitness, FitnessRetrieve, Generate, Mutate},
 };
 use rand::{rngs::StdRng, Rng, SeedableRng};
@@ -16,16 +17,13 @@ const MAX_VALUE: i64 = 255;
 
 impl Fitness for IntegerArray {
     fn calculate_fitness(&mut self, _seed: [u8; 32]) -> Option<f64> {
-        if self.0.get_fitness().is_none() {
-            let res: i64 = self
-                .0
-                .get_data()
-                .iter()
-                .map(|v| if v.0 == 0 { 1 } else { 0 })
-                .sum();
-            self.0.set_fitness(Some(res as f64));
-            return Some(res as f64);
-        }
+        let res: i64 = self
+            .0
+            .get_data()
+            .iter()
+            .map(|v| if v.0 == 0 { 1 } else { 0 })
+            .sum();
+        self.0.set_fitness(Some(res as f64));
         self.0.get_fitness()
     }
 }
@@ -52,15 +50,19 @@ impl Generate for IntegerArray {
 }
 
 impl Crossover for IntegerArray {
-    fn crossover(&self, other: &Self, seed: [u8; 32]) -> Self {
-        IntegerArray(self.0.crossover(&other.0, seed))
+    fn crossover(&self, other: &Self, config: &CrossoverConfig, seed: [u8; 32]) -> Self {
+        IntegerArray(self.0.crossover(&other.0, config, seed))
     }
 }
 
 impl FitnessRetrieve for IntegerArray {
+    type Value = f64;
     fn get_fitness(&self) -> Option<f64> {
         self.0.get_fitness()
     }
+    fn set_fitness(&mut self, fitness: Option<f64>) {
+        self.0.set_fitness(fitness);
+    }
 }
 
 impl Mutate for IntegerArray {
@@ -69,6 +71,7 @@ impl Mutate for IntegerArray {
     }
 }
 
+#[allow(clippy::derivable_impls)]
 impl Default for IntegerArray {
     fn default() -> Self {
         IntegerArray(ItemArray::default())
@@ -76,21 +79,25 @@ impl Default for IntegerArray {
 }
 
 fn main() {
-    let config = PopulationConfig {
-        pop_size: 10,
-        crossover_count: 2,
-        mutate_count: 2,
-        elitism_count: 2,
-        mutation_config: MutationConfig {
+    let config = PopulationConfig::builder()
+        .pop_size(10)
+        .crossover_count(2)
+        .mutate_count(2)
+        .elitism_count(2)
+        .mutation_config(MutationConfig {
             gene_mutation_chance: 0.3,
-        },
-        seed: rand::thread_rng().gen(),
-    };
+            ..Default::default()
+        })
+        .seed(rand::thread_rng().gen())
+        .build()
+        .expect("valid config");
     let mut p: Population<IntegerArray> = Population::new(config);
 
-    (0..10000).for_each(|i| {
-        p.tick();
-        let best = p.get_best_member();
-        println!("Gen {i}: {:?}", best);
-    });
+    let report = p
+        .run(&Termination::MaxGenerations(10000).or(Termination::Stagnation(1000)))
+        .expect("nothing missing for this config");
+    println!(
+        "Stopped after {} generations ({:?}): {:?}",
+        report.generations, report.reason, report.best
+    );
 }
@@ -1,284 +1,98 @@
-use std::{collections::VecDeque, rc::Rc};
+use std::sync::OnceLock;
 
 use ga::{
-    population::{Genome, MutationConfig, Population, PopulationConfig},
-    traits::{Crossover, Fitness, FitnessRetrieve, Generate, Mutate},
+    gp::{PrimitiveSet, Primitives, Tree},
+    niching::Niching,
+    objective::Objective,
+    population::{CrossoverConfig, MutationConfig, Population, PopulationConfig},
+    selection::SelectionMethod,
+    termination::Termination,
+    traits::{Crossover, Distance, Fitness, FitnessRetrieve, Generate, Mutate},
 };
-use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand::Rng;
 
 pub const MAX_DEPTH: usize = 5;
-pub const NR_VARS: usize = 2;
 pub const MAX_VALUE: i64 = 255;
 pub const MIN_VALUE: i64 = -255;
 
-type Child = Option<Rc<Node>>;
-
-#[derive(Default, Clone, Debug)]
-pub struct Tree {
-    pub root: Child,
-}
-
-#[derive(Default, Clone, Debug)]
-pub struct Node {
-    value: String,
-    left: Child,
-    right: Child,
-}
-
-impl Node {
-    pub fn evaluate(&self, x1: f64, x2: f64) -> f64 {
-        let left = self.left.as_ref().map(|f| f.evaluate(x1, x2));
-        let right = self.right.as_ref().map(|f| f.evaluate(x1, x2));
-        match (left, right) {
-            (Some(left), Some(right)) => {
-                match (self.value.get(0..1).unwrap_or("bork"), self.value.len()) {
-                    ("+", 1) => left + right,
-                    ("-", 1) => left - right,
-                    ("*", 1) => left * right,
-                    ("/", 1) => {
-                        if right == 0.0 {
-                            0.0
-                        } else {
-                            left / right
-                        }
-                    }
-                    ("^", 1) => left.powf(right),
-                    _ => match self.value.parse() {
-                        Ok(v) => v,
-                        _ => 0.0,
-                    },
-                }
-            }
-            (None, None) => match self.value.get(0..1).unwrap_or("bork") {
-                "X" => match self.value.get(1..2).unwrap() {
-                    "0" => x1,
-                    "1" => x2,
-                    _ => 0.0,
-                },
-                _ => match self.value.parse() {
-                    Ok(v) => v,
-                    _ => 0.0,
-                },
-            },
-            _ => 0.0,
-        }
-    }
-
-    pub fn print(&self) -> String {
-        match (self.left.as_ref(), self.right.as_ref()) {
-            (Some(l), Some(r)) => {
-                let l_res = l.print();
-                let r_res = r.print();
-
-                let mut res = String::from("(");
-                res.push_str(&l_res);
-                res.push_str(&self.value);
-                res.push_str(&r_res);
-                res.push_str(")");
-                res
-            }
-            _ => self.value.clone(),
-        }
-    }
-
-    pub fn get_nth_node(&self, n: usize) -> Child {
-        let mut queue: VecDeque<Child> = VecDeque::new();
-        queue.push_back(Some(Rc::new(self.clone())));
-        let mut cindex = 0;
-
-        let mut last_element: Child = None;
-        while let Some(current_node) = queue.pop_front() {
-            if current_node.is_some() {
-                if cindex == n {
-                    return current_node;
-                }
-                cindex += 1;
-                last_element = current_node;
-
-                if let (Some(l), Some(r)) = (&self.left, &self.right) {
-                    queue.push_back(Some(l.clone()));
-                    queue.push_back(Some(r.clone()));
-                }
-            }
-        }
-        last_element
-    }
-
-    pub fn node_count(&self) -> usize {
-        if let (Some(l), Some(r)) = (&self.left, &self.right) {
-            1 + l.node_count() + r.node_count()
-        } else {
-            1
-        }
-    }
-
-    pub fn depth(&self) -> usize {
-        if let (Some(l), Some(r)) = (&self.left, &self.right) {
-            1 + std::cmp::max(l.depth(), r.depth())
-        } else {
-            0
-        }
-    }
-
-    pub fn new(value: String, left: Child, right: Child) -> Child {
-        Some(Rc::new(Node { value, left, right }))
-    }
-}
-
-impl Tree {
-    pub fn new(root: Child) -> Self {
-        Tree { root }
+struct Arithmetic;
+
+impl Primitives for Arithmetic {
+    fn primitive_set() -> &'static PrimitiveSet {
+        static SET: OnceLock<PrimitiveSet> = OnceLock::new();
+        SET.get_or_init(|| {
+            PrimitiveSet::new()
+                .function("+", 2, |a| a[0] + a[1])
+                .function("-", 2, |a| a[0] - a[1])
+                .function("*", 2, |a| a[0] * a[1])
+                .function("/", 2, |a| if a[1] == 0.0 { 0.0 } else { a[0] / a[1] })
+                .function("^", 2, |a| a[0].powf(a[1]))
+                .ephemeral("int", |rng| rng.gen_range(MIN_VALUE..MAX_VALUE) as f64)
+                .variable("X0")
+                .variable("X1")
+                .init_depth(1, MAX_DEPTH)
+                .max_depth(MAX_DEPTH)
+        })
     }
 }
 
 #[derive(Default, Debug, Clone)]
-struct GATree {
-    inner: Genome<Tree>,
-}
+struct GATree(Tree<Arithmetic>);
 
 impl Mutate for GATree {
     fn mutate(&self, config: &MutationConfig, seed: [u8; 32]) -> Self {
-        let tree = &self.inner.data;
-
-        fn traverse(root: &Child, config: &MutationConfig, seed: [u8; 32]) -> Child {
-            let mut rng: StdRng = SeedableRng::from_seed(seed);
-
-            match root {
-                None => None,
-                Some(node) => {
-                    if rng.gen::<i32>() % 100 < ((100.0 * config.gene_mutation_chance) as i32) {
-                        random_node(node.depth(), rng.gen())
-                    } else {
-                        let new_left = traverse(&node.left, config, rng.gen());
-                        let new_right = traverse(&node.right, config, rng.gen());
-                        Node::new(node.value.clone(), new_left, new_right)
-                    }
-                }
-            }
-        }
-
-        let new_root = traverse(&tree.root, config, seed);
-        GATree {
-            inner: Genome {
-                data: Tree { root: new_root },
-                ..Default::default()
-            },
-        }
+        GATree(self.0.mutate(config, seed))
     }
 }
 
 impl Crossover for GATree {
-    fn crossover(&self, other: &Self, seed: [u8; 32]) -> Self {
-        let mut rng: StdRng = SeedableRng::from_seed(seed);
-
-        let tree = self.inner.data.root.clone().unwrap();
-        let crossover_tree = &other.inner.data.root.as_ref();
-
-        let cross_point = rng.gen_range(0..tree.node_count());
-        let origin_node = tree.get_nth_node(cross_point);
-        let cross_node = crossover_tree
-            .unwrap()
-            .get_nth_node(rng.gen_range(0..crossover_tree.unwrap().node_count()));
-
-        fn traverse(root: &Child, origin_node: &Child, cross_node: &Child) -> Child {
-            match (root, origin_node) {
-                (None, _) => None,
-                (Some(node), None) => {
-                    Node::new(node.value.clone(), node.left.clone(), node.right.clone())
-                }
-                (Some(node), Some(origin)) => {
-                    if node.print() == origin.print() {
-                        cross_node.clone()
-                    } else {
-                        let new_left = traverse(&node.left, origin_node, cross_node);
-                        let new_right = traverse(&node.right, origin_node, cross_node);
-
-                        Node::new(node.value.clone(), new_left, new_right)
-                    }
-                }
-            }
-        }
-
-        let new_root = traverse(&self.inner.data.root, &origin_node, &cross_node);
-
-        let data: Tree = Tree::new(new_root);
-        GATree {
-            inner: Genome {
-                data,
-                ..Default::default()
-            },
-        }
+    fn crossover(&self, other: &Self, config: &CrossoverConfig, seed: [u8; 32]) -> Self {
+        GATree(self.0.crossover(&other.0, config, seed))
     }
 }
 
 impl Generate for GATree {
     fn generate(seed: [u8; 32]) -> Self {
-        let root = random_node(MAX_DEPTH, seed);
-        GATree {
-            inner: Genome {
-                data: Tree::new(root),
-                ..Default::default()
-            },
-        }
+        GATree(Tree::generate(seed))
+    }
+}
+
+impl Distance for GATree {
+    fn distance(&self, other: &Self) -> f64 {
+        self.0.distance(&other.0)
     }
 }
 
 impl FitnessRetrieve for GATree {
+    type Value = f64;
     fn get_fitness(&self) -> Option<f64> {
-        self.inner.fitness
+        self.0.get_fitness()
+    }
+    fn set_fitness(&mut self, fitness: Option<f64>) {
+        self.0.set_fitness(fitness);
     }
 }
 
 impl Fitness for GATree {
+    #[allow(clippy::identity_op)]
     fn calculate_fitness(&mut self, _seed: [u8; 32]) -> Option<f64> {
-        let tree = &self.inner.data;
         let mut wrong: f64 = 0.0;
         (0..10).for_each(|i| {
-            (0..10).for_each(|y| match &tree.root {
-                None => wrong += 3.0,
-                Some(root) => {
-                    let actual = root.evaluate(i as f64, y as f64) % (i64::MAX as f64);
-                    // This is the function we're trying to approximate
-                    let real = i * i + y * y + 0;
-                    let diff = (real - actual.round() as i64).abs();
-                    match diff {
-                        0 => {}
-                        1..=100 => wrong += diff as f64,
-                        _ => wrong += 1000.0,
-                    }
+            (0..10).for_each(|y| {
+                let actual = self.0.evaluate(&[i as f64, y as f64]) % (i64::MAX as f64);
+                // This is the function we're trying to approximate
+                let real = i * i + y * y + 0;
+                let diff = (real - actual.round() as i64).abs();
+                match diff {
+                    0 => {}
+                    1..=100 => wrong += diff as f64,
+                    _ => wrong += 1000.0,
                 }
             });
         });
-        self.inner.fitness = Some(0.0 - wrong);
+        self.0.set_fitness(Some(wrong));
 
-        self.inner.fitness
-    }
-}
-
-pub fn random_node(depth: usize, seed: [u8; 32]) -> Child {
-    let mut rng: StdRng = SeedableRng::from_seed(seed);
-    let mut val = rng.gen_range(0..7);
-    if depth <= 1 {
-        val = rng.gen_range(0..2);
-    }
-
-    match (val, NR_VARS) {
-        (0, _) => Node::new(rng.gen_range(MIN_VALUE..MAX_VALUE).to_string(), None, None),
-        (1, 0) => Node::new(rng.gen_range(MIN_VALUE..MAX_VALUE).to_string(), None, None),
-        (1, _) => {
-            let var = rng.gen_range(0..NR_VARS);
-            let mut var_name = "X".to_owned();
-            var_name.push_str(var.to_string().as_str());
-            Node::new(var_name, None, None)
-        }
-        (2..=6, _) => {
-            let ops = vec!['+', '-', '*', '/', '^'];
-            let value = ops[val - 2].to_string();
-            let left = random_node(depth - 1, rng.gen());
-            let right = random_node(depth - 1, rng.gen());
-            Node::new(value, left, right)
-        }
-        _ => Node::new("borked".to_string(), None, None),
+        self.0.get_fitness()
     }
 }
 
@@ -290,18 +104,26 @@ fn main() {
         elitism_count: 6,
         mutation_config: MutationConfig {
             gene_mutation_chance: 0.3,
+            ..Default::default()
         },
         seed: rand::thread_rng().gen(),
+        objective: Objective::Minimize,
+        selection: SelectionMethod::Tournament { size: 3 },
+        // Keeps several lineages of trees alive instead of converging on one.
+        niching: Some(Niching::Speciation { threshold: 6.0 }),
+        ..Default::default()
     };
     let mut p: Population<GATree> = Population::new(config);
-
-    (0..1000).for_each(|i| {
-        p.tick();
-        let best = p.get_best_member();
-        println!(
-            "Gen {i}: Fitness: {} - {:?}",
-            best.get_fitness().unwrap(),
-            best.inner.data.root.clone().unwrap().print()
-        );
-    });
+    p.use_genome_distance();
+
+    let report = p
+        .run(&Termination::MaxGenerations(1000).or(Termination::TargetFitness(0.0)))
+        .expect("the distance is set");
+    println!(
+        "Gen {}: Fitness: {} - {:?} ({:?})",
+        report.generations,
+        report.best.get_fitness().unwrap(),
+        report.best.0.print(),
+        report.reason
+    );
 }
@@ -0,0 +1,744 @@
+//! Island model: several populations evolving side by side that exchange
+//! members every few generations.
+
+use std::{collections::BTreeMap, fmt, fs, path::Path, time::Instant};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    checkpoint::{self, Checkpoint, CheckpointError, CheckpointFormat, CHECKPOINT_VERSION},
+    noise::RunningStats,
+    objective::Objective,
+    population::{ConfigError, Population, PopulationConfig},
+    replacement::ReplacementPolicy,
+    selection::EmigrantSelection,
+    stats::GenerationStats,
+    termination::{RunProgress, RunReport, Termination},
+    traits::{Crossover, Distance, Fitness, FitnessRetrieve, FitnessValue, Generate, Mutate},
+};
+
+/// Which islands send members to which.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Topology {
+    /// Each island sends to the next one, and the last to the first.
+    #[default]
+    Ring,
+    /// Each island sends to every other island.
+    FullyConnected,
+    /// The hub sends to every other island, and they send only to the hub.
+    Star { hub: usize },
+    /// `Custom(edges)` sends from island `i` to each island in `edges[i]`.
+    /// Islands missing from the list send nothing.
+    Custom(Vec<Vec<usize>>),
+}
+
+impl Topology {
+    /// The islands `island` sends to, out of `islands` in total. An island
+    /// never sends to itself.
+    pub fn destinations(&self, island: usize, islands: usize) -> Vec<usize> {
+        let destinations = match self {
+            Topology::Ring => vec![(island + 1) % islands],
+            Topology::FullyConnected => (0..islands).collect(),
+            Topology::Star { hub } if island == *hub => (0..islands).collect(),
+            Topology::Star { hub } => vec![*hub],
+            Topology::Custom(edges) => edges.get(island).cloned().unwrap_or_default(),
+        };
+        destinations.into_iter().filter(|d| *d != island).collect()
+    }
+
+    fn check(&self, islands: usize) -> Result<(), ArchipelagoError> {
+        let largest = match self {
+            Topology::Ring | Topology::FullyConnected => None,
+            Topology::Star { hub } => Some(*hub),
+            Topology::Custom(edges) => edges
+                .len()
+                .checked_sub(1)
+                .into_iter()
+                .chain(edges.iter().flatten().copied())
+                .max(),
+        };
+        match largest {
+            Some(island) if island >= islands => {
+                Err(ArchipelagoError::UnknownIsland { island, islands })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MigrationConfig {
+    /// Migrates after every this many generations. 0 never migrates.
+    pub interval: usize,
+    /// Members each island sends to each of its destinations.
+    pub count: usize,
+    #[serde(default)]
+    pub topology: Topology,
+    #[serde(default)]
+    pub emigrants: EmigrantSelection,
+    /// The members immigrants replace. Elites are never replaced.
+    #[serde(default)]
+    pub replacement: ReplacementPolicy,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArchipelagoError {
+    NoIslands,
+    /// An island's population config is invalid.
+    Island {
+        island: usize,
+        error: ConfigError,
+    },
+    /// The topology names an island that does not exist.
+    UnknownIsland {
+        island: usize,
+        islands: usize,
+    },
+    /// Emigrants are selected, or immigrants replace members, with a
+    /// tournament of 0 or a truncation proportion outside `(0, 1]`.
+    InvalidMigration(MigrationConfig),
+}
+
+impl fmt::Display for ArchipelagoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchipelagoError::NoIslands => write!(f, "an archipelago needs at least one island"),
+            ArchipelagoError::Island { island, error } => {
+                write!(f, "island {}: {}", island, error)
+            }
+            ArchipelagoError::UnknownIsland { island, islands } => write!(
+                f,
+                "the topology names island {}, but there are only {} islands",
+                island, islands
+            ),
+            ArchipelagoError::InvalidMigration(migration) => write!(
+                f,
+                "{:?} needs tournaments of at least 1 and a proportion above 0 and at most 1",
+                migration
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ArchipelagoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ArchipelagoError::Island { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Copies `config` for each of `islands` islands, each with its own seed drawn
+/// from `seed`.
+pub fn island_configs(
+    config: &PopulationConfig,
+    islands: usize,
+    seed: [u8; 32],
+) -> Vec<PopulationConfig> {
+    let mut rng: StdRng = SeedableRng::from_seed(seed);
+    (0..islands)
+        .map(|_| PopulationConfig {
+            seed: rng.gen(),
+            ..config.clone()
+        })
+        .collect()
+}
+
+/// Populations ticked together, exchanging members as set by a
+/// `MigrationConfig`. Each island keeps its own config and seed; migration
+/// draws from the archipelago's seed, so runs are repeatable.
+///
+/// Immigrants keep the fitness they had on their home island, so islands
+/// should share a fitness function and objective.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Archipelago<T: Generate + Crossover + Mutate + Fitness + FitnessRetrieve + Default> {
+    pub islands: Vec<Population<T>>,
+    pub migration: MigrationConfig,
+    generation: i64,
+    seed: [u8; 32],
+}
+
+impl<T: Generate + Crossover + Mutate + Fitness + FitnessRetrieve + Default + Clone>
+    Archipelago<T>
+{
+    /// Generates every island. The configs are not checked; see `try_new`.
+    pub fn new(
+        configs: Vec<PopulationConfig>,
+        migration: MigrationConfig,
+        seed: [u8; 32],
+    ) -> Archipelago<T> {
+        Archipelago {
+            islands: configs.into_iter().map(Population::new).collect(),
+            migration,
+            generation: 1,
+            seed,
+        }
+    }
+
+    /// Like `new`, but refuses invalid island configs and migration, and
+    /// topologies that name missing islands.
+    pub fn try_new(
+        configs: Vec<PopulationConfig>,
+        migration: MigrationConfig,
+        seed: [u8; 32],
+    ) -> Result<Archipelago<T>, ArchipelagoError> {
+        if configs.is_empty() {
+            return Err(ArchipelagoError::NoIslands);
+        }
+        for (island, config) in configs.iter().enumerate() {
+            config
+                .validate()
+                .map_err(|error| ArchipelagoError::Island { island, error })?;
+        }
+        migration.topology.check(configs.len())?;
+        let selection_valid = match &migration.emigrants {
+            EmigrantSelection::Best => true,
+            EmigrantSelection::Select(selection) => selection.is_valid(),
+        };
+        if !selection_valid || !migration.replacement.is_valid() {
+            return Err(ArchipelagoError::InvalidMigration(migration));
+        }
+        Ok(Archipelago::new(configs, migration, seed))
+    }
+
+    pub fn get_generation(&self) -> i64 {
+        self.generation
+    }
+
+    /// Fitness evaluations made by all islands so far.
+    pub fn get_evaluations(&self) -> u64 {
+        self.islands.iter().map(|i| i.get_evaluations()).sum()
+    }
+
+    /// Stats of all islands' members together, ranked by the first island's
+    /// objective, without evaluating them. Diversity is measured with the
+    /// first island's distance and gene entropy once it is enabled there.
+    pub fn current_stats(&self) -> GenerationStats {
+        let fitnesses: Vec<Option<T::Value>> = self
+            .islands
+            .iter()
+            .flat_map(|i| i.members.iter().map(|m| m.get_fitness()))
+            .collect();
+        let diversity = self
+            .islands
+            .first()
+            .filter(|i| i.measures_diversity())
+            .map(|first| {
+                let members: Vec<T> = self
+                    .islands
+                    .iter()
+                    .flat_map(|i| i.members.iter().cloned())
+                    .collect();
+                first.diversity(&members)
+            });
+        GenerationStats {
+            total_evaluations: self.get_evaluations(),
+            diversity,
+            ..GenerationStats::new(self.generation - 1, &fitnesses, self.objective())
+        }
+    }
+
+    /// Measures diversity on every island and for the islands together; see
+    /// `Population::enable_diversity`.
+    pub fn enable_diversity(&mut self) {
+        self.islands.iter_mut().for_each(|i| i.enable_diversity());
+    }
+
+    /// The best member of all islands, or `None` if no island has members.
+    pub fn get_best_member(&mut self) -> Option<&T> {
+        let objective = self.objective();
+        let best = self
+            .islands
+            .iter_mut()
+            .filter(|i| !i.members.is_empty())
+            .map(|i| i.get_best_member())
+            .min_by(|a, b| objective.compare(&a.get_fitness(), &b.get_fitness()))?;
+        Some(best)
+    }
+
+    /// Checks every island with `Population::validate`.
+    pub fn validate(&self) -> Result<(), ArchipelagoError> {
+        for (island, population) in self.islands.iter().enumerate() {
+            population
+                .validate()
+                .map_err(|error| ArchipelagoError::Island { island, error })?;
+        }
+        Ok(())
+    }
+
+    /// Sets the distance of every island; see `Population::set_distance`.
+    pub fn set_distance(&mut self, distance: fn(&T, &T) -> f64) {
+        self.islands
+            .iter_mut()
+            .for_each(|i| i.set_distance(distance));
+    }
+
+    /// Ticks every island, then migrates if the interval is up. Each island's
+    /// own stats go to its history and observers as usual. Panics if
+    /// `validate` fails; `tick_with_stats` returns the error instead.
+    pub fn tick(&mut self) {
+        if let Err(error) = self.tick_with_stats() {
+            panic!("{}", error);
+        }
+    }
+
+    /// Like `tick`, and returns the stats of all islands together, or the
+    /// error `tick` panics with.
+    pub fn tick_with_stats(&mut self) -> Result<GenerationStats, ArchipelagoError> {
+        self.validate()?;
+        let started = Instant::now();
+        let (mut evaluations, mut cache_hits) = (0, 0);
+        for (i, island) in self.islands.iter_mut().enumerate() {
+            let stats = island
+                .tick_with_stats()
+                .map_err(|error| ArchipelagoError::Island { island: i, error })?;
+            evaluations += stats.evaluations;
+            cache_hits += stats.cache_hits;
+        }
+        let interval = self.migration.interval as i64;
+        if interval > 0 && self.generation % interval == 0 {
+            self.migrate();
+        }
+        self.generation += 1;
+        Ok(GenerationStats {
+            evaluations,
+            cache_hits,
+            duration: started.elapsed(),
+            ..self.current_stats()
+        })
+    }
+
+    /// Sends emigrants along the topology right away. All islands pick their
+    /// emigrants before any immigrants arrive.
+    pub fn migrate(&mut self) {
+        let mut rng: StdRng = SeedableRng::from_seed(self.seed);
+        let islands = self.islands.len();
+        let mut incoming: Vec<Vec<(T, Option<RunningStats>)>> =
+            (0..islands).map(|_| Vec::new()).collect();
+        for (i, island) in self.islands.iter_mut().enumerate() {
+            for destination in self.migration.topology.destinations(i, islands) {
+                incoming[destination].extend(island.emigrants(
+                    &self.migration.emigrants,
+                    self.migration.count,
+                    rng.gen(),
+                ));
+            }
+        }
+        for (island, immigrants) in self.islands.iter_mut().zip(incoming) {
+            island.immigrate(immigrants, self.migration.replacement, &mut rng);
+        }
+        self.seed = rng.gen();
+    }
+
+    /// Ticks until `termination` is met by the islands together, or an
+    /// island's observer asks to stop, and reports the best member. Fails
+    /// without islands, if an island fails `Population::validate` or can't
+    /// measure the diversity `termination` checks, or if no island has members
+    /// to report.
+    pub fn run(
+        &mut self,
+        termination: &Termination<T::Value>,
+    ) -> Result<RunReport<T, T::Value>, ArchipelagoError> {
+        if self.islands.is_empty() {
+            return Err(ArchipelagoError::NoIslands);
+        }
+        self.validate()?;
+        for (island, population) in self.islands.iter().enumerate() {
+            population
+                .check_measured(termination)
+                .map_err(|error| ArchipelagoError::Island { island, error })?;
+        }
+        if termination.uses_diversity() {
+            self.enable_diversity();
+        }
+        let mut progress =
+            RunProgress::new(&self.current_stats(), self.best_fitness(), self.objective());
+        let reason = loop {
+            if let Some(reason) = termination.check(&progress) {
+                break reason;
+            }
+            let stats = self.tick_with_stats()?;
+            progress.update(&stats, self.best_fitness());
+            if self.islands.iter().any(|i| i.stop_requested()) {
+                break Termination::ObserverStop;
+            }
+        };
+
+        Ok(RunReport {
+            reason,
+            best: self
+                .get_best_member()
+                .cloned()
+                .ok_or(ArchipelagoError::NoIslands)?,
+            generations: self.generation - 1,
+            evaluations: self.get_evaluations(),
+            elapsed: progress.started.elapsed(),
+        })
+    }
+
+    fn best_fitness(&self) -> Option<T::Value> {
+        let objective = self.objective();
+        self.islands
+            .iter()
+            .fold(None, |best, i| objective.best(best, i.best_fitness()))
+    }
+
+    fn objective(&self) -> Objective {
+        self.islands
+            .first()
+            .map(|i| i.config.objective)
+            .unwrap_or_default()
+    }
+}
+
+impl<T: Generate + Crossover + Mutate + Fitness + FitnessRetrieve + Default + Clone + Distance>
+    Archipelago<T>
+{
+    /// Uses the genome's own `Distance` on every island.
+    pub fn use_genome_distance(&mut self) {
+        self.islands
+            .iter_mut()
+            .for_each(|i| i.use_genome_distance());
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<T: Generate + Crossover + Mutate + Fitness + FitnessRetrieve + Default + Clone + Send>
+    Archipelago<T>
+{
+    /// Evaluates every island's members across threads; see
+    /// `Population::use_parallel_evaluation`.
+    pub fn use_parallel_evaluation(&mut self) {
+        self.islands
+            .iter_mut()
+            .for_each(|i| i.use_parallel_evaluation());
+    }
+}
+
+/// Checkpoints of every island, saved and loaded together.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchipelagoCheckpoint<T, F = f64> {
+    /// Must stay the first field, so the version can be read before the rest.
+    pub version: u32,
+    pub metadata: BTreeMap<String, String>,
+    pub migration: MigrationConfig,
+    /// Seed of the migration RNG.
+    pub seed: [u8; 32],
+    pub generation: i64,
+    pub islands: Vec<Checkpoint<T, F>>,
+}
+
+impl<T: Serialize + DeserializeOwned, F: FitnessValue> ArchipelagoCheckpoint<T, F> {
+    pub fn save(
+        &self,
+        path: impl AsRef<Path>,
+        format: CheckpointFormat,
+    ) -> Result<(), CheckpointError> {
+        fs::write(path, checkpoint::encode(self, format)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>, format: CheckpointFormat) -> Result<Self, CheckpointError> {
+        checkpoint::decode(&fs::read(path)?, format)
+    }
+}
+
+impl<
+        T: Generate
+            + Crossover
+            + Mutate
+            + Fitness
+            + FitnessRetrieve
+            + Default
+            + Clone
+            + Serialize
+            + DeserializeOwned,
+    > Archipelago<T>
+{
+    pub fn to_checkpoint(
+        &self,
+        metadata: BTreeMap<String, String>,
+    ) -> ArchipelagoCheckpoint<T, T::Value> {
+        ArchipelagoCheckpoint {
+            version: CHECKPOINT_VERSION,
+            metadata,
+            migration: self.migration.clone(),
+            seed: self.seed,
+            generation: self.generation,
+            islands: self
+                .islands
+                .iter()
+                .map(|i| i.to_checkpoint(BTreeMap::new()))
+                .collect(),
+        }
+    }
+
+    /// Resumes every island. As with `Population::from_checkpoint`, observers
+    /// and fitness caches must be added again.
+    pub fn from_checkpoint(checkpoint: ArchipelagoCheckpoint<T, T::Value>) -> Archipelago<T> {
+        Archipelago {
+            islands: checkpoint
+                .islands
+                .into_iter()
+                .map(Population::from_checkpoint)
+                .collect(),
+            migration: checkpoint.migration,
+            generation: checkpoint.generation,
+            seed: checkpoint.seed,
+        }
+    }
+
+    pub fn save_checkpoint(
+        &self,
+        path: impl AsRef<Path>,
+        format: CheckpointFormat,
+        metadata: BTreeMap<String, String>,
+    ) -> Result<(), CheckpointError> {
+        self.to_checkpoint(metadata).save(path, format)
+    }
+
+    pub fn load_checkpoint(
+        path: impl AsRef<Path>,
+        format: CheckpointFormat,
+    ) -> Result<Archipelago<T>, CheckpointError> {
+        Ok(Self::from_checkpoint(ArchipelagoCheckpoint::load(
+            path, format,
+        )?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{island_configs, Archipelago, ArchipelagoError, MigrationConfig, Topology};
+    use crate::{
+        checkpoint::{read_metadata, CheckpointFormat, TempPath},
+        diversity::DiversityMetric,
+        population::{ConfigError, MutationConfig, PopulationConfig},
+        problems::OneMax,
+        replacement::{Replacement, ReplacementPolicy},
+        selection::{EmigrantSelection, SelectionMethod},
+        termination::{RestartConfig, Termination},
+    };
+
+    #[test]
+    fn test_topology() {
+        assert_eq!(vec![1], Topology::Ring.destinations(0, 3));
+        assert_eq!(vec![0], Topology::Ring.destinations(2, 3));
+        assert!(Topology::Ring.destinations(0, 1).is_empty());
+        assert_eq!(vec![0, 2], Topology::FullyConnected.destinations(1, 3));
+        let star = Topology::Star { hub: 1 };
+        assert_eq!(vec![0, 2], star.destinations(1, 3));
+        assert_eq!(vec![1], star.destinations(2, 3));
+        let custom = Topology::Custom(vec![vec![0, 2], vec![]]);
+        assert_eq!(vec![2], custom.destinations(0, 3));
+        assert!(custom.destinations(2, 3).is_empty());
+    }
+
+    fn configs(islands: usize) -> Vec<PopulationConfig> {
+        let config = PopulationConfig {
+            pop_size: 10,
+            crossover_count: 4,
+            mutate_count: 4,
+            elitism_count: 1,
+            mutation_config: MutationConfig {
+                gene_mutation_chance: 0.05,
+                ..Default::default()
+            },
+            selection: SelectionMethod::Tournament { size: 2 },
+            ..Default::default()
+        };
+        island_configs(&config, islands, [3; 32])
+    }
+
+    fn migration() -> MigrationConfig {
+        MigrationConfig {
+            interval: 2,
+            count: 2,
+            replacement: ReplacementPolicy::Random,
+            ..Default::default()
+        }
+    }
+
+    fn state(archipelago: &Archipelago<OneMax>) -> String {
+        serde_json::to_string(
+            &archipelago
+                .islands
+                .iter()
+                .map(|i| &i.members)
+                .collect::<Vec<_>>(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_migration() {
+        let mut archipelago: Archipelago<OneMax> =
+            Archipelago::try_new(configs(3), migration(), [0; 32]).unwrap();
+        assert_ne!(
+            serde_json::to_string(&archipelago.islands[0].members).unwrap(),
+            serde_json::to_string(&archipelago.islands[1].members).unwrap()
+        );
+        archipelago.tick();
+        let best = serde_json::to_string(archipelago.islands[0].get_best_member()).unwrap();
+        archipelago.migrate();
+        assert!(archipelago.islands[1]
+            .members
+            .iter()
+            .any(|m| serde_json::to_string(m).unwrap() == best));
+
+        let report = archipelago.run(&Termination::MaxGenerations(10)).unwrap();
+        assert_eq!(10, report.generations);
+        assert_eq!(
+            archipelago.get_evaluations(),
+            archipelago
+                .islands
+                .iter()
+                .map(|i| i.get_evaluations())
+                .sum::<u64>()
+        );
+
+        let mut again: Archipelago<OneMax> = Archipelago::new(configs(3), migration(), [0; 32]);
+        again.tick();
+        again.migrate();
+        again.run(&Termination::MaxGenerations(10)).unwrap();
+        assert_eq!(state(&archipelago), state(&again));
+    }
+
+    #[test]
+    fn test_invalid() {
+        let star = MigrationConfig {
+            topology: Topology::Star { hub: 3 },
+            ..migration()
+        };
+        assert_eq!(
+            Some(ArchipelagoError::UnknownIsland {
+                island: 3,
+                islands: 3
+            }),
+            Archipelago::<OneMax>::try_new(configs(3), star, [0; 32]).err()
+        );
+        let mut bad = configs(2);
+        bad[1].pop_size = 1;
+        assert_eq!(
+            Some(ArchipelagoError::Island {
+                island: 1,
+                error: ConfigError::PopulationTooSmall { pop_size: 1 }
+            }),
+            Archipelago::<OneMax>::try_new(bad, migration(), [0; 32]).err()
+        );
+
+        let mut crowding = configs(2);
+        crowding[1].replacement = Replacement::DeterministicCrowding;
+        let mut archipelago: Archipelago<OneMax> = Archipelago::new(crowding, migration(), [0; 32]);
+        let missing = Some(ArchipelagoError::Island {
+            island: 1,
+            error: ConfigError::MissingDistance,
+        });
+        assert_eq!(missing, archipelago.tick_with_stats().err());
+        assert_eq!(
+            missing,
+            archipelago.run(&Termination::MaxGenerations(3)).err()
+        );
+        archipelago.use_genome_distance();
+        assert!(archipelago.tick_with_stats().is_ok());
+
+        let truncation = MigrationConfig {
+            emigrants: EmigrantSelection::Select(SelectionMethod::Truncation { proportion: 0.0 }),
+            ..migration()
+        };
+        assert_eq!(
+            Some(ArchipelagoError::InvalidMigration(truncation.clone())),
+            Archipelago::<OneMax>::try_new(configs(2), truncation, [0; 32]).err()
+        );
+        let loser = MigrationConfig {
+            replacement: ReplacementPolicy::TournamentLoser { size: 0 },
+            ..migration()
+        };
+        assert_eq!(
+            Some(ArchipelagoError::InvalidMigration(loser.clone())),
+            Archipelago::<OneMax>::try_new(configs(2), loser, [0; 32]).err()
+        );
+
+        let mut empty: Archipelago<OneMax> = Archipelago::new(vec![], migration(), [0; 32]);
+        empty.tick();
+        assert!(empty.get_best_member().is_none());
+        assert_eq!(
+            Some(ArchipelagoError::NoIslands),
+            empty.run(&Termination::MaxGenerations(3)).err()
+        );
+    }
+
+    #[test]
+    fn test_diversity() {
+        let mut archipelago: Archipelago<OneMax> =
+            Archipelago::new(configs(2), migration(), [0; 32]);
+        let low = Termination::LowDiversity {
+            metric: DiversityMetric::Unique,
+            threshold: 21.0,
+        };
+        assert_eq!(
+            Some(ArchipelagoError::Island {
+                island: 0,
+                error: ConfigError::UnmeasuredDiversity(DiversityMetric::Unique),
+            }),
+            archipelago.run(&low).err()
+        );
+        assert_eq!(None, archipelago.current_stats().diversity);
+        archipelago.use_genome_distance();
+        // Fewer than 21 members are always unique, so the run stops untouched.
+        let report = archipelago
+            .run(&low.clone().or(Termination::MaxGenerations(5)))
+            .unwrap();
+        assert_eq!((low, 0), (report.reason, report.generations));
+        let diversity = archipelago.current_stats().diversity.unwrap();
+        assert!((1..=20).contains(&diversity.unique.unwrap()));
+
+        // Islands restart on their own, keeping one member each time.
+        let run = |restart: Option<RestartConfig>| {
+            let configs = configs(2)
+                .into_iter()
+                .map(|config| PopulationConfig {
+                    restart: restart.clone(),
+                    ..config
+                })
+                .collect();
+            let mut archipelago: Archipelago<OneMax> =
+                Archipelago::new(configs, migration(), [0; 32]);
+            archipelago.run(&Termination::MaxGenerations(5)).unwrap()
+        };
+        let restarted = run(Some(RestartConfig {
+            trigger: Termination::Stagnation(0),
+            keep: 1,
+        }));
+        assert_eq!(run(None).evaluations + 2 * 4 * 9, restarted.evaluations);
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        let mut archipelago: Archipelago<OneMax> =
+            Archipelago::new(configs(3), migration(), [0; 32]);
+        archipelago.tick();
+
+        let path = TempPath::new("archipelago.json");
+        let metadata = BTreeMap::from([("problem".to_string(), "onemax".to_string())]);
+        archipelago
+            .save_checkpoint(&path, CheckpointFormat::Json, metadata.clone())
+            .unwrap();
+        assert_eq!(
+            metadata,
+            read_metadata(&path, CheckpointFormat::Json).unwrap()
+        );
+        let mut resumed: Archipelago<OneMax> =
+            Archipelago::load_checkpoint(&path, CheckpointFormat::Json).unwrap();
+        for _ in 0..4 {
+            archipelago.tick();
+            resumed.tick();
+        }
+        assert_eq!(archipelago.get_generation(), resumed.get_generation());
+        assert_eq!(state(&archipelago), state(&resumed));
+    }
+}
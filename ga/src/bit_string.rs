@@ -0,0 +1,384 @@
+use std::ops::Range;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    crossover::{cut_points, ArrayCrossover},
+    population::{probability, CrossoverConfig, Genome, MutationConfig},
+    traits::{Crossover, Distance, FitnessRetrieve, FitnessValue, Generate, Mutate},
+};
+
+pub const DEFAULT_LEN: usize = 64;
+
+const WORD_BITS: usize = 64;
+
+/// A string of bits packed into `u64` words. Bit `i` lives in word `i / 64`
+/// at position `i % 64`; unused bits of the last word are always zero.
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct BitString<F = f64> {
+    inner: Genome<Vec<u64>, F>,
+    len: usize,
+}
+
+impl<F: FitnessValue> BitString<F> {
+    pub fn zeros(len: usize) -> Self {
+        BitString {
+            inner: Genome {
+                data: vec![0; words_for(len)],
+                ..Default::default()
+            },
+            len,
+        }
+    }
+    pub fn from_bits(bits: &[bool]) -> Self {
+        let mut bit_string = BitString::zeros(bits.len());
+        bits.iter()
+            .enumerate()
+            .for_each(|(i, b)| bit_string.set(i, *b));
+        bit_string
+    }
+    pub fn generate_length(len: usize, seed: [u8; 32]) -> Self {
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let mut bit_string = BitString::zeros(len);
+        bit_string
+            .inner
+            .data
+            .iter_mut()
+            .for_each(|w| *w = rng.gen());
+        bit_string.clear_tail();
+        bit_string
+    }
+    pub fn get_words(&self) -> &Vec<u64> {
+        &self.inner.data
+    }
+    /// Number of times a fitness has been set on this genome.
+    pub fn get_evaluations(&self) -> u64 {
+        self.inner.evaluations
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    pub fn get(&self, index: usize) -> bool {
+        assert!(
+            index < self.len,
+            "bit {index} out of range for length {}",
+            self.len
+        );
+        self.inner.data[index / WORD_BITS] >> (index % WORD_BITS) & 1 == 1
+    }
+    pub fn set(&mut self, index: usize, value: bool) {
+        assert!(
+            index < self.len,
+            "bit {index} out of range for length {}",
+            self.len
+        );
+        let mask = 1 << (index % WORD_BITS);
+        if value {
+            self.inner.data[index / WORD_BITS] |= mask;
+        } else {
+            self.inner.data[index / WORD_BITS] &= !mask;
+        }
+    }
+    pub fn count_ones(&self) -> usize {
+        self.inner
+            .data
+            .iter()
+            .map(|w| w.count_ones() as usize)
+            .sum()
+    }
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|i| self.get(i))
+    }
+
+    /// Reads `range` as an unsigned integer, most significant bit first.
+    /// The range may be at most 64 bits long.
+    pub fn decode(&self, range: Range<usize>) -> u64 {
+        assert!(range.len() <= WORD_BITS, "cannot decode more than 64 bits");
+        range.fold(0, |acc, i| (acc << 1) | self.get(i) as u64)
+    }
+    /// Reads `range` as a Gray-coded unsigned integer.
+    pub fn decode_gray(&self, range: Range<usize>) -> u64 {
+        gray_to_binary(self.decode(range))
+    }
+    /// Maps the Gray-coded integer in `range` linearly onto `[lower, upper]`.
+    pub fn decode_gray_f64(&self, range: Range<usize>, lower: f64, upper: f64) -> f64 {
+        let bits = range.len();
+        if bits == 0 {
+            return lower;
+        }
+        let max = if bits == WORD_BITS {
+            u64::MAX
+        } else {
+            (1 << bits) - 1
+        };
+        lower + (upper - lower) * (self.decode_gray(range) as f64 / max as f64)
+    }
+
+    fn clear_tail(&mut self) {
+        if !self.len.is_multiple_of(WORD_BITS) {
+            if let Some(last) = self.inner.data.last_mut() {
+                *last &= (1 << (self.len % WORD_BITS)) - 1;
+            }
+        }
+    }
+    fn from_words(data: Vec<u64>, len: usize) -> Self {
+        let mut bit_string = BitString {
+            inner: Genome {
+                data,
+                ..Default::default()
+            },
+            len,
+        };
+        bit_string.clear_tail();
+        bit_string
+    }
+}
+
+pub fn binary_to_gray(value: u64) -> u64 {
+    value ^ (value >> 1)
+}
+
+pub fn gray_to_binary(gray: u64) -> u64 {
+    let mut value = gray;
+    let mut shift = 1;
+    while shift < WORD_BITS {
+        value ^= value >> shift;
+        shift <<= 1;
+    }
+    value
+}
+
+fn words_for(len: usize) -> usize {
+    len.div_ceil(WORD_BITS)
+}
+
+/// Sets bits `start..end` of `mask`.
+fn set_range(mask: &mut [u64], start: usize, end: usize) {
+    (start..end).for_each(|i| mask[i / WORD_BITS] |= 1 << (i % WORD_BITS));
+}
+
+impl<F: FitnessValue> Generate for BitString<F> {
+    fn generate(seed: [u8; 32]) -> Self {
+        BitString::generate_length(DEFAULT_LEN, seed)
+    }
+}
+
+impl<F: FitnessValue> Mutate for BitString<F> {
+    /// Flips each bit independently with `gene_mutation_chance`.
+    fn mutate(&self, config: &MutationConfig, seed: [u8; 32]) -> Self {
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let chance = probability(config.gene_mutation_chance);
+        let mut data = self.inner.data.clone();
+        (0..self.len).for_each(|i| {
+            if rng.gen_bool(chance) {
+                data[i / WORD_BITS] ^= 1 << (i % WORD_BITS);
+            }
+        });
+        BitString::from_words(data, self.len)
+    }
+}
+
+impl<F: FitnessValue> Crossover for BitString<F> {
+    fn crossover(&self, other: &Self, config: &CrossoverConfig, seed: [u8; 32]) -> Self {
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let min_length = std::cmp::min(self.len, other.len);
+
+        // Bits set in `mask` come from `self`, the rest from `other`.
+        let (mask, len) = match &config.array {
+            ArrayCrossover::SinglePoint => {
+                self.segment_mask(other, &[rng.gen_range(0..=min_length)])
+            }
+            ArrayCrossover::TwoPoint => {
+                self.segment_mask(other, &cut_points(min_length, 2, &mut rng))
+            }
+            ArrayCrossover::KPoint { points } => {
+                self.segment_mask(other, &cut_points(min_length, *points, &mut rng))
+            }
+            ArrayCrossover::Uniform { bias } => {
+                let bias = probability(*bias);
+                let len = if rng.gen_bool(bias) {
+                    self.len
+                } else {
+                    other.len
+                };
+                let mut mask = vec![0; words_for(len)];
+                if bias == 0.5 {
+                    mask[..words_for(min_length)]
+                        .iter_mut()
+                        .for_each(|w| *w = rng.gen());
+                    if !min_length.is_multiple_of(WORD_BITS) {
+                        mask[min_length / WORD_BITS] &= (1 << (min_length % WORD_BITS)) - 1;
+                    }
+                } else {
+                    (0..min_length).for_each(|i| {
+                        if rng.gen_bool(bias) {
+                            mask[i / WORD_BITS] |= 1 << (i % WORD_BITS);
+                        }
+                    });
+                }
+                if len == self.len {
+                    set_range(&mut mask, min_length, len);
+                }
+                (mask, len)
+            }
+            ArrayCrossover::CutAndSplice => {
+                let first_cut = rng.gen_range(0..=self.len);
+                let second_cut = rng.gen_range(0..=other.len);
+                let mut offspring = BitString::zeros(first_cut + other.len - second_cut);
+                (0..first_cut).for_each(|i| offspring.set(i, self.get(i)));
+                (second_cut..other.len)
+                    .enumerate()
+                    .for_each(|(i, j)| offspring.set(first_cut + i, other.get(j)));
+                return offspring;
+            }
+        };
+
+        let data = mask
+            .iter()
+            .enumerate()
+            .map(|(i, m)| {
+                let a = self.inner.data.get(i).copied().unwrap_or(0);
+                let b = other.inner.data.get(i).copied().unwrap_or(0);
+                (a & m) | (b & !m)
+            })
+            .collect();
+        BitString::from_words(data, len)
+    }
+}
+
+impl<F: FitnessValue> BitString<F> {
+    /// Mask taking alternate segments from `self` and `other`, starting with
+    /// `self`, along with the offspring length.
+    fn segment_mask(&self, other: &Self, points: &[usize]) -> (Vec<u64>, usize) {
+        let from_self_at_end = points.len().is_multiple_of(2);
+        let len = if from_self_at_end {
+            self.len
+        } else {
+            other.len
+        };
+        let mut mask = vec![0; words_for(len)];
+        let mut start = 0;
+        let mut from_self = true;
+        for &point in points {
+            if from_self {
+                set_range(&mut mask, start, point);
+            }
+            start = point;
+            from_self = !from_self;
+        }
+        if from_self {
+            set_range(&mut mask, start, len);
+        }
+        (mask, len)
+    }
+}
+
+impl<F: FitnessValue> FitnessRetrieve for BitString<F> {
+    type Value = F;
+    fn get_fitness(&self) -> Option<F> {
+        self.inner.fitness.clone()
+    }
+    fn set_fitness(&mut self, fitness: Option<F>) {
+        self.inner.set_fitness(fitness);
+    }
+}
+
+/// Hamming distance, with the shorter string padded with zeros.
+impl<F> Distance for BitString<F> {
+    fn distance(&self, other: &Self) -> f64 {
+        let (a, b) = (&self.inner.data, &other.inner.data);
+        (0..a.len().max(b.len()))
+            .map(|i| {
+                let x = a.get(i).copied().unwrap_or(0) ^ b.get(i).copied().unwrap_or(0);
+                x.count_ones() as f64
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{binary_to_gray, gray_to_binary, BitString};
+    use crate::{
+        crossover::ArrayCrossover,
+        population::{CrossoverConfig, MutationConfig},
+        traits::{Crossover, Distance, Mutate},
+    };
+
+    #[test]
+    fn test_gray_code() {
+        (0..1024).for_each(|v| assert_eq!(v, gray_to_binary(binary_to_gray(v))));
+        assert_eq!(u64::MAX, gray_to_binary(binary_to_gray(u64::MAX)));
+
+        let bits: BitString = BitString::from_bits(&[true, true, false, true, false]);
+        assert_eq!(0b1101, bits.decode(0..4));
+        assert_eq!(0b1001, bits.decode_gray(0..4));
+        assert!((1.0 / 3.0 - bits.decode_gray_f64(0..2, -1.0, 1.0)).abs() < 1e-12);
+        assert_eq!(1.0, bits.decode_gray_f64(3..5, -1.0, 1.0));
+    }
+
+    #[test]
+    fn test_mutation_rate() {
+        let bits: BitString = BitString::zeros(130);
+        let config = MutationConfig {
+            gene_mutation_chance: 1.0,
+            ..Default::default()
+        };
+        let mutated = bits.mutate(&config, [1; 32]);
+        assert_eq!(130, mutated.count_ones());
+        assert_eq!(3, mutated.get_words().len());
+        assert_eq!(130.0, bits.distance(&mutated));
+        assert_eq!(0.0, mutated.distance(&mutated));
+    }
+
+    #[test]
+    fn test_crossover_matches_array_crossover() {
+        let first: BitString = BitString::from_bits(&[true; 100]);
+        let second = BitString::zeros(140);
+        let operators = [
+            ArrayCrossover::SinglePoint,
+            ArrayCrossover::TwoPoint,
+            ArrayCrossover::KPoint { points: 5 },
+        ];
+        for array in operators {
+            let config = CrossoverConfig {
+                array: array.clone(),
+                ..Default::default()
+            };
+            let offspring = first.crossover(&second, &config, [9; 32]);
+            let expected = array.apply(
+                &first.iter().collect::<Vec<_>>(),
+                &second.iter().collect::<Vec<_>>(),
+                [9; 32],
+            );
+            assert_eq!(expected, offspring.iter().collect::<Vec<_>>(), "{array:?}");
+        }
+
+        let config = CrossoverConfig {
+            array: ArrayCrossover::Uniform { bias: 1.0 },
+            ..Default::default()
+        };
+        let offspring = first.crossover(&second, &config, [9; 32]);
+        assert_eq!(100, offspring.count_ones());
+        assert_eq!(100, offspring.len());
+
+        let config = CrossoverConfig {
+            array: ArrayCrossover::Uniform { bias: 0.5 },
+            ..Default::default()
+        };
+        let offspring = second.crossover(&first, &config, [9; 32]);
+        assert!(offspring.iter().skip(100).all(|b| !b));
+
+        let config = CrossoverConfig {
+            array: ArrayCrossover::CutAndSplice,
+            ..Default::default()
+        };
+        let offspring = first.crossover(&second, &config, [9; 32]);
+        let ones = offspring.count_ones();
+        assert!(offspring.iter().take(ones).all(|b| b));
+    }
+}
@@ -0,0 +1,116 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+type KeyHash<T> = Box<dyn Fn(&T) -> u64 + Send + Sync>;
+type SameKey<T> = Box<dyn Fn(&T, &T) -> bool + Send + Sync>;
+
+/// Evaluated members stored by a key of their content, so that a new member
+/// with an equal key takes the cached member's fitness instead of being evaluated.
+///
+/// The key function decides what counts as a duplicate. Keys are compared with
+/// `Eq`, so members whose keys merely hash alike are never mixed up. The cache
+/// is emptied whenever it reaches `capacity`.
+pub struct FitnessCache<T> {
+    hash: KeyHash<T>,
+    same: SameKey<T>,
+    capacity: usize,
+    entries: HashMap<u64, Vec<T>>,
+    len: usize,
+}
+
+impl<T: Clone> FitnessCache<T> {
+    pub fn new<K: Hash + Eq + 'static>(key: fn(&T) -> K, capacity: usize) -> Self
+    where
+        T: 'static,
+    {
+        FitnessCache {
+            hash: Box::new(move |member| hash_key(&key(member))),
+            same: Box::new(move |a, b| key(a) == key(b)),
+            capacity,
+            entries: HashMap::new(),
+            len: 0,
+        }
+    }
+
+    /// Hash of the member's key, for grouping members before `same_key`.
+    pub fn hash(&self, member: &T) -> u64 {
+        (self.hash)(member)
+    }
+
+    pub fn same_key(&self, a: &T, b: &T) -> bool {
+        (self.same)(a, b)
+    }
+
+    /// The cached member with the same key as `member`, whose key hash is `hash`.
+    pub fn get(&self, hash: u64, member: &T) -> Option<&T> {
+        self.entries
+            .get(&hash)?
+            .iter()
+            .find(|cached| self.same_key(cached, member))
+    }
+
+    pub fn insert(&mut self, hash: u64, member: T) {
+        if self.len >= self.capacity {
+            self.clear();
+        }
+        let bucket = self.entries.entry(hash).or_default();
+        match bucket
+            .iter()
+            .position(|cached| (self.same)(cached, &member))
+        {
+            Some(i) => bucket[i] = member,
+            None => {
+                bucket.push(member);
+                self.len += 1;
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.len = 0;
+    }
+}
+
+impl<T> fmt::Debug for FitnessCache<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FitnessCache")
+            .field("capacity", &self.capacity)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+fn hash_key<K: Hash>(key: &K) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FitnessCache;
+
+    #[test]
+    fn test_colliding_keys() {
+        // Every key hashes alike here, so only `Eq` tells the members apart.
+        let mut cache: FitnessCache<(u8, Option<f64>)> = FitnessCache::new(|m| m.0, 10);
+        cache.insert(0, (1, Some(1.0)));
+        cache.insert(0, (2, Some(2.0)));
+        assert_eq!(2, cache.len());
+        assert_eq!(Some(&(2, Some(2.0))), cache.get(0, &(2, None)));
+        assert_eq!(None, cache.get(0, &(3, None)));
+        assert!(cache.same_key(&(1, None), &(1, Some(1.0))));
+    }
+}
@@ -0,0 +1,251 @@
+//! Versioned snapshots of a `Population` that can be written to disk and
+//! resumed later.
+
+use std::{collections::BTreeMap, fmt, fs, io, path::Path};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    noise::RunningStats, population::PopulationConfig, stats::History, traits::FitnessValue,
+};
+
+/// Version written by this build. JSON checkpoints from earlier versions are
+/// read with the fields added since left empty; binary checkpoints and later
+/// versions are refused.
+pub const CHECKPOINT_VERSION: u32 = 3;
+
+/// Everything needed to resume a population, plus free-form user metadata.
+/// `F` is the members' fitness type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint<T, F = f64> {
+    /// Must stay the first field, so the version can be read before the rest.
+    pub version: u32,
+    pub metadata: BTreeMap<String, String>,
+    pub config: PopulationConfig,
+    pub members: Vec<T>,
+    /// Fitness of each member when the checkpoint was taken, in the same order.
+    pub fitnesses: Vec<Option<F>>,
+    /// Fitness samples of each member when noise handling is enabled. Added in version 2.
+    #[serde(default)]
+    pub samples: Vec<RunningStats>,
+    /// Generation each member was created in, in steady-state mode. Added in version 3.
+    #[serde(default)]
+    pub born: Vec<i64>,
+    /// Seed of the population's RNG for the next generation.
+    pub seed: [u8; 32],
+    pub generation: i64,
+    pub evaluations: u64,
+    pub history: Option<History>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckpointFormat {
+    Json,
+    /// Compact bincode encoding.
+    #[cfg(feature = "binary")]
+    Binary,
+}
+
+impl CheckpointFormat {
+    /// `Binary` for `.bin` files when the `binary` feature is enabled, `Json` otherwise.
+    pub fn from_path(path: impl AsRef<Path>) -> CheckpointFormat {
+        match path.as_ref().extension().and_then(|e| e.to_str()) {
+            #[cfg(feature = "binary")]
+            Some("bin") => CheckpointFormat::Binary,
+            _ => CheckpointFormat::Json,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CheckpointError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    #[cfg(feature = "binary")]
+    Binary(bincode::Error),
+    /// The checkpoint was written with a format version this build can't read.
+    UnsupportedVersion {
+        found: u32,
+        supported: u32,
+    },
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckpointError::Io(e) => write!(f, "checkpoint I/O error: {}", e),
+            CheckpointError::Json(e) => write!(f, "invalid JSON checkpoint: {}", e),
+            #[cfg(feature = "binary")]
+            CheckpointError::Binary(e) => write!(f, "invalid binary checkpoint: {}", e),
+            CheckpointError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "checkpoint version {} is not supported (this build writes version {})",
+                found, supported
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CheckpointError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CheckpointError::Io(e) => Some(e),
+            CheckpointError::Json(e) => Some(e),
+            #[cfg(feature = "binary")]
+            CheckpointError::Binary(e) => Some(e),
+            CheckpointError::UnsupportedVersion { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for CheckpointError {
+    fn from(e: io::Error) -> Self {
+        CheckpointError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for CheckpointError {
+    fn from(e: serde_json::Error) -> Self {
+        CheckpointError::Json(e)
+    }
+}
+
+#[cfg(feature = "binary")]
+impl From<bincode::Error> for CheckpointError {
+    fn from(e: bincode::Error) -> Self {
+        CheckpointError::Binary(e)
+    }
+}
+
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+/// The fields that come before the member type is needed.
+#[derive(Deserialize)]
+struct Preamble {
+    version: u32,
+    metadata: BTreeMap<String, String>,
+}
+
+fn check_version(version: u32, format: CheckpointFormat) -> Result<(), CheckpointError> {
+    let readable = match format {
+        CheckpointFormat::Json => (1..=CHECKPOINT_VERSION).contains(&version),
+        #[cfg(feature = "binary")]
+        CheckpointFormat::Binary => version == CHECKPOINT_VERSION,
+    };
+    if !readable {
+        return Err(CheckpointError::UnsupportedVersion {
+            found: version,
+            supported: CHECKPOINT_VERSION,
+        });
+    }
+    Ok(())
+}
+
+/// Reads the metadata of a population or archipelago checkpoint without
+/// knowing its member type, for example to find out which problem it belongs to.
+pub fn read_metadata(
+    path: impl AsRef<Path>,
+    format: CheckpointFormat,
+) -> Result<BTreeMap<String, String>, CheckpointError> {
+    let bytes = fs::read(path)?;
+    let preamble: Preamble = match format {
+        CheckpointFormat::Json => serde_json::from_slice(&bytes)?,
+        #[cfg(feature = "binary")]
+        CheckpointFormat::Binary => bincode::deserialize(&bytes)?,
+    };
+    check_version(preamble.version, format)?;
+    Ok(preamble.metadata)
+}
+
+/// Encodes any versioned snapshot whose first field is its version.
+pub(crate) fn encode<S: Serialize>(
+    snapshot: &S,
+    format: CheckpointFormat,
+) -> Result<Vec<u8>, CheckpointError> {
+    Ok(match format {
+        CheckpointFormat::Json => serde_json::to_vec(snapshot)?,
+        #[cfg(feature = "binary")]
+        CheckpointFormat::Binary => bincode::serialize(snapshot)?,
+    })
+}
+
+/// Decodes a snapshot written by `encode`, checking its version before
+/// anything else.
+pub(crate) fn decode<S: DeserializeOwned>(
+    bytes: &[u8],
+    format: CheckpointFormat,
+) -> Result<S, CheckpointError> {
+    let version = match format {
+        CheckpointFormat::Json => serde_json::from_slice::<Header>(bytes)?.version,
+        #[cfg(feature = "binary")]
+        CheckpointFormat::Binary => bincode::deserialize::<u32>(bytes)?,
+    };
+    check_version(version, format)?;
+    Ok(match format {
+        CheckpointFormat::Json => serde_json::from_slice(bytes)?,
+        #[cfg(feature = "binary")]
+        CheckpointFormat::Binary => bincode::deserialize(bytes)?,
+    })
+}
+
+impl<T: Serialize + DeserializeOwned, F: FitnessValue> Checkpoint<T, F> {
+    pub fn to_bytes(&self, format: CheckpointFormat) -> Result<Vec<u8>, CheckpointError> {
+        encode(self, format)
+    }
+
+    /// Decodes a checkpoint, checking its version before anything else.
+    pub fn from_bytes(bytes: &[u8], format: CheckpointFormat) -> Result<Self, CheckpointError> {
+        decode(bytes, format)
+    }
+
+    pub fn save(
+        &self,
+        path: impl AsRef<Path>,
+        format: CheckpointFormat,
+    ) -> Result<(), CheckpointError> {
+        fs::write(path, self.to_bytes(format)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>, format: CheckpointFormat) -> Result<Self, CheckpointError> {
+        Self::from_bytes(&fs::read(path)?, format)
+    }
+}
+
+/// A file in the temp directory, unique to the test process and `name`, that
+/// is removed when dropped, even if the test fails.
+#[cfg(test)]
+pub(crate) struct TempPath(std::path::PathBuf);
+
+#[cfg(test)]
+impl TempPath {
+    pub(crate) fn new(name: &str) -> TempPath {
+        let file = format!("ga_test_{}_{}", std::process::id(), name);
+        TempPath(std::env::temp_dir().join(file))
+    }
+}
+
+#[cfg(test)]
+impl std::ops::Deref for TempPath {
+    type Target = Path;
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl AsRef<Path> for TempPath {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
@@ -0,0 +1,138 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::population::probability;
+
+/// Crossover operators for array-like genomes.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ArrayCrossover {
+    #[default]
+    SinglePoint,
+    TwoPoint,
+    KPoint {
+        points: usize,
+    },
+    /// Each gene comes from the first parent with probability `bias`.
+    Uniform {
+        bias: f64,
+    },
+    /// Cuts each parent at its own point and splices the head of the first
+    /// onto the tail of the second, so the offspring length can change.
+    CutAndSplice,
+}
+
+impl ArrayCrossover {
+    pub fn apply<T: Clone>(&self, first: &[T], second: &[T], seed: [u8; 32]) -> Vec<T> {
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let min_length = std::cmp::min(first.len(), second.len());
+
+        match self {
+            ArrayCrossover::SinglePoint => k_point(first, second, &[rng.gen_range(0..=min_length)]),
+            ArrayCrossover::TwoPoint => {
+                k_point(first, second, &cut_points(min_length, 2, &mut rng))
+            }
+            ArrayCrossover::KPoint { points } => {
+                k_point(first, second, &cut_points(min_length, *points, &mut rng))
+            }
+            ArrayCrossover::Uniform { bias } => {
+                let mut offspring: Vec<T> = first
+                    .iter()
+                    .zip(second.iter())
+                    .map(|(a, b)| {
+                        if rng.gen_bool(probability(*bias)) {
+                            a
+                        } else {
+                            b
+                        }
+                    })
+                    .cloned()
+                    .collect();
+                let tail = if rng.gen_bool(probability(*bias)) {
+                    first
+                } else {
+                    second
+                };
+                offspring.extend_from_slice(&tail[min_length.min(tail.len())..]);
+                offspring
+            }
+            ArrayCrossover::CutAndSplice => {
+                let first_cut = rng.gen_range(0..=first.len());
+                let second_cut = rng.gen_range(0..=second.len());
+                let mut offspring = Vec::with_capacity(first_cut + second.len() - second_cut);
+                offspring.extend_from_slice(&first[..first_cut]);
+                offspring.extend_from_slice(&second[second_cut..]);
+                offspring
+            }
+        }
+    }
+}
+
+/// `count` sorted cut points in `0..=length`, possibly repeated.
+pub fn cut_points(length: usize, count: usize, rng: &mut StdRng) -> Vec<usize> {
+    let mut points: Vec<usize> = (0..count).map(|_| rng.gen_range(0..=length)).collect();
+    points.sort_unstable();
+    points
+}
+
+/// Alternates between the parents at every cut point, starting with `first`.
+/// Genes past the end of the shorter parent come from whichever parent is
+/// active for the last segment.
+pub fn k_point<T: Clone>(first: &[T], second: &[T], points: &[usize]) -> Vec<T> {
+    let mut offspring = Vec::with_capacity(std::cmp::max(first.len(), second.len()));
+    let mut start = 0;
+    let mut from_first = true;
+    for &point in points {
+        let source = if from_first { first } else { second };
+        offspring.extend_from_slice(&source[start.min(source.len())..point.min(source.len())]);
+        start = point;
+        from_first = !from_first;
+    }
+    let source = if from_first { first } else { second };
+    offspring.extend_from_slice(&source[start.min(source.len())..]);
+    offspring
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{k_point, ArrayCrossover};
+
+    #[test]
+    fn test_k_point() {
+        let a = [0, 0, 0, 0, 0, 0];
+        let b = [1, 1, 1, 1, 1, 1, 1, 1];
+        assert_eq!(vec![0, 0, 1, 1, 1, 1, 1, 1], k_point(&a, &b, &[2]));
+        assert_eq!(vec![0, 1, 1, 1, 0, 0], k_point(&a, &b, &[1, 4]));
+        assert_eq!(vec![0, 1, 0, 0, 0, 1, 1, 1], k_point(&a, &b, &[1, 2, 5]));
+        assert_eq!(a.to_vec(), k_point(&a, &b, &[3, 3]));
+    }
+
+    #[test]
+    fn test_operators_keep_genes_from_parents() {
+        let a = [0; 10];
+        let b = [1; 14];
+        let operators = [
+            ArrayCrossover::SinglePoint,
+            ArrayCrossover::TwoPoint,
+            ArrayCrossover::KPoint { points: 5 },
+            ArrayCrossover::Uniform { bias: 0.5 },
+        ];
+        for operator in operators {
+            let offspring = operator.apply(&a, &b, [7; 32]);
+            assert!(
+                offspring.len() == 10 || offspring.len() == 14,
+                "{operator:?}"
+            );
+        }
+
+        let offspring = ArrayCrossover::Uniform { bias: 1.0 }.apply(&a, &b, [7; 32]);
+        assert_eq!(a.to_vec(), offspring);
+        // Unchecked configs can hold a NaN bias, which takes every gene from `b`.
+        let offspring = ArrayCrossover::Uniform { bias: f64::NAN }.apply(&a, &b, [7; 32]);
+        assert_eq!(b.to_vec(), offspring);
+
+        let offspring = ArrayCrossover::CutAndSplice.apply(&a, &b, [7; 32]);
+        let zeros = offspring.iter().take_while(|g| **g == 0).count();
+        assert!(offspring[zeros..].iter().all(|g| *g == 1));
+        assert!(offspring.len() <= a.len() + b.len());
+    }
+}
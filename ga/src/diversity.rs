@@ -0,0 +1,121 @@
+//! Measures of how varied a population is, to tell when a run has converged.
+
+use serde::{Deserialize, Serialize};
+
+use crate::traits::Genes;
+
+/// How varied the members are at the end of a generation. Recorded in
+/// `GenerationStats` after `Population::enable_diversity`.
+///
+/// Measures are `None` unless the population can compute them:
+/// `mean_distance` and `unique` need `Population::set_distance`, and
+/// `gene_entropy` needs `Population::use_gene_entropy`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diversity {
+    /// Mean distance over all pairs of members.
+    pub mean_distance: Option<f64>,
+    /// Members at a distance above 0 from every member before them.
+    pub unique: Option<usize>,
+    /// Mean Shannon entropy, in bits, of the genes at each position.
+    pub gene_entropy: Option<f64>,
+}
+
+/// One of the measures of `Diversity`, for stop and restart conditions. The
+/// spread of fitness is `Termination::DiversityCollapse`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DiversityMetric {
+    MeanDistance,
+    Unique,
+    GeneEntropy,
+}
+
+impl Diversity {
+    pub fn get(&self, metric: DiversityMetric) -> Option<f64> {
+        match metric {
+            DiversityMetric::MeanDistance => self.mean_distance,
+            DiversityMetric::Unique => self.unique.map(|u| u as f64),
+            DiversityMetric::GeneEntropy => self.gene_entropy,
+        }
+    }
+}
+
+/// The mean distance over all pairs of `len` members, `None` with fewer than
+/// two, and the number of unique members.
+pub fn pairwise(len: usize, distance: impl Fn(usize, usize) -> f64) -> (Option<f64>, usize) {
+    let mut duplicate = vec![false; len];
+    let mut total = 0.0;
+    for i in 0..len {
+        for (j, duplicate) in duplicate.iter_mut().enumerate().skip(i + 1) {
+            let d = distance(i, j);
+            total += d;
+            *duplicate |= d <= 0.0;
+        }
+    }
+    let pairs = len * len.saturating_sub(1) / 2;
+    let mean = (pairs > 0).then(|| total / pairs as f64);
+    (mean, duplicate.iter().filter(|d| !**d).count())
+}
+
+/// Mean Shannon entropy, in bits, of the genes at each position, over the
+/// members long enough to have that position. 0 when every member is alike.
+pub fn gene_entropy<T: Genes>(members: &[T]) -> f64 {
+    let positions = members.iter().map(|m| m.genes().len()).max().unwrap_or(0);
+    if positions == 0 {
+        return 0.0;
+    }
+    let total: f64 = (0..positions)
+        .map(|position| {
+            let genes: Vec<&T::Gene> = members
+                .iter()
+                .filter_map(|m| m.genes().get(position))
+                .collect();
+            let mut counts: Vec<(&T::Gene, usize)> = Vec::new();
+            for gene in &genes {
+                match counts.iter_mut().find(|(g, _)| g == gene) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((gene, 1)),
+                }
+            }
+            counts
+                .iter()
+                .map(|(_, count)| {
+                    let p = *count as f64 / genes.len() as f64;
+                    -p * p.log2()
+                })
+                .sum::<f64>()
+        })
+        .sum();
+    total / positions as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{gene_entropy, pairwise};
+    use crate::traits::Genes;
+
+    struct Row(Vec<u8>);
+
+    impl Genes for Row {
+        type Gene = u8;
+        fn genes(&self) -> &[u8] {
+            &self.0
+        }
+    }
+
+    #[test]
+    fn test_diversity() {
+        let positions = [0.0, 0.0, 3.0];
+        let (mean, unique) = pairwise(3, |i, j| f64::abs(positions[i] - positions[j]));
+        assert_eq!((Some(2.0), 2), (mean, unique));
+        assert_eq!((None, 1), pairwise(1, |_, _| 0.0));
+
+        let rows: Vec<Row> = [[0, 1, 2], [0, 2, 1], [0, 2, 1], [0, 1, 2]]
+            .iter()
+            .map(|genes| Row(genes.to_vec()))
+            .collect();
+        // The first position never varies, the other two split evenly.
+        assert!((gene_entropy(&rows) - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!(0.0, gene_entropy(&rows[1..3]));
+        assert_eq!(0.0, gene_entropy::<Row>(&[]));
+    }
+}
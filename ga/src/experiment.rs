@@ -0,0 +1,574 @@
+//! Experiments described in configuration files and run by problem name.
+//!
+//! An experiment names a problem from a `Registry` and gives the population
+//! config, termination and logging. The registry only holds problems, each of
+//! which fixes the genome type and objective; operators, selection and
+//! replacement are the built-in ones, chosen by their serde names. Custom
+//! operators come with a genome type of their own, registered as a problem.
+//! For example in TOML:
+//!
+//! ```toml
+//! problem = "onemax"
+//! seed = 7
+//! termination = { Any = [{ MaxGenerations = 200 }, { TargetFitness = 64.0 }] }
+//!
+//! [population]
+//! pop_size = 50
+//! crossover_count = 20
+//! mutate_count = 20
+//! elitism_count = 2
+//! selection = { Tournament = { size = 3 } }
+//! mutation_config = { gene_mutation_chance = 0.02 }
+//! crossover_config = { array = "TwoPoint" }
+//!
+//! [logging]
+//! every = 10
+//! history_csv = "onemax.csv"
+//! ```
+//!
+//! A file may instead hold a list of experiments under `experiments`, for
+//! parameter sweeps. JSON files are always supported; TOML and YAML need the
+//! `toml` and `yaml` features. YAML writes enum variants as tags, such as
+//! `termination: !MaxGenerations 200`.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Debug},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    checkpoint::{CheckpointError, CheckpointFormat},
+    objective::Objective,
+    observer::ProgressLogger,
+    population::{ConfigError, Population, PopulationConfig},
+    problems,
+    termination::Termination,
+    traits::{Crossover, Distance, Fitness, FitnessRetrieve, Generate, Mutate},
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentConfig {
+    #[serde(default)]
+    pub name: String,
+    /// Name of the problem in the `Registry`.
+    pub problem: String,
+    /// Replaces `population.seed` with a seed expanded from this number.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    pub population: PopulationConfig,
+    pub termination: Termination,
+    #[serde(default)]
+    pub logging: LoggingConfig,
+    /// Continues the population saved in this checkpoint instead of starting a
+    /// new one. The checkpoint's own config is used, so `population` and
+    /// `seed` are ignored.
+    #[serde(default)]
+    pub resume: Option<PathBuf>,
+}
+
+/// Progress output and history files for an experiment.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct LoggingConfig {
+    /// Prints a progress line to stderr every this many generations; 0 never does.
+    #[serde(default)]
+    pub every: usize,
+    #[serde(default)]
+    pub history_csv: Option<PathBuf>,
+    #[serde(default)]
+    pub history_json: Option<PathBuf>,
+    /// Saves the population here when the run ends, in binary if the path
+    /// ends in `.bin` and the `binary` feature is enabled, and JSON otherwise.
+    #[serde(default)]
+    pub checkpoint: Option<PathBuf>,
+    /// Measures the population's diversity every generation, for the history
+    /// and progress lines.
+    #[serde(default)]
+    pub diversity: bool,
+}
+
+#[derive(Deserialize)]
+struct Sweep {
+    experiments: Vec<ExperimentConfig>,
+}
+
+impl ExperimentConfig {
+    /// The population config with `seed` applied.
+    pub fn population_config(&self) -> PopulationConfig {
+        let mut config = self.population.clone();
+        if let Some(seed) = self.seed {
+            config.seed = StdRng::seed_from_u64(seed).gen();
+        }
+        config
+    }
+
+    /// Reads the experiments in `path`, choosing the format by its extension.
+    pub fn load_all(path: impl AsRef<Path>) -> Result<Vec<ExperimentConfig>, ExperimentError> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)?;
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("json") => Self::from_json(&contents),
+            #[cfg(feature = "toml")]
+            Some("toml") => Self::from_toml(&contents),
+            #[cfg(feature = "yaml")]
+            Some("yaml" | "yml") => Self::from_yaml(&contents),
+            _ => Err(ExperimentError::UnsupportedFormat(path.to_path_buf())),
+        }
+    }
+
+    pub fn from_json(contents: &str) -> Result<Vec<ExperimentConfig>, ExperimentError> {
+        let value: serde_json::Value = serde_json::from_str(contents)?;
+        if value.get("experiments").is_some() {
+            Ok(serde_json::from_value::<Sweep>(value)?.experiments)
+        } else {
+            Ok(vec![serde_json::from_value(value)?])
+        }
+    }
+
+    #[cfg(feature = "toml")]
+    pub fn from_toml(contents: &str) -> Result<Vec<ExperimentConfig>, ExperimentError> {
+        // Parsed again as the target type so that errors keep their line numbers.
+        let table: toml::Table = toml::from_str(contents)?;
+        if table.contains_key("experiments") {
+            Ok(toml::from_str::<Sweep>(contents)?.experiments)
+        } else {
+            Ok(vec![toml::from_str(contents)?])
+        }
+    }
+
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml(contents: &str) -> Result<Vec<ExperimentConfig>, ExperimentError> {
+        let value: serde_yaml::Value = serde_yaml::from_str(contents)?;
+        if value.get("experiments").is_some() {
+            Ok(serde_yaml::from_str::<Sweep>(contents)?.experiments)
+        } else {
+            Ok(vec![serde_yaml::from_str(contents)?])
+        }
+    }
+}
+
+/// Outcome of one experiment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExperimentReport {
+    pub name: String,
+    pub problem: String,
+    pub reason: Termination,
+    pub generations: i64,
+    pub evaluations: u64,
+    pub best_fitness: Option<f64>,
+    /// `Debug` output of the best member.
+    pub best: String,
+}
+
+/// Runs one experiment; usually `run_problem` for some genome.
+pub type Runner = fn(&ExperimentConfig) -> Result<ExperimentReport, ExperimentError>;
+
+/// Problems that experiments can name, each with its objective and the
+/// function that runs it. `Registry::default()` holds the problems in `problems`.
+///
+/// ```
+/// use ga::experiment::{run_problem, Registry};
+/// use ga::objective::Objective;
+/// use ga::problems::OneMax;
+///
+/// let mut registry = Registry::empty();
+/// registry.register("bits", Objective::Maximize, run_problem::<OneMax>);
+/// assert_eq!(vec!["bits"], registry.names().collect::<Vec<_>>());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Registry {
+    problems: BTreeMap<String, (Objective, Runner)>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Registry::empty();
+        problems::register(&mut registry);
+        registry
+    }
+}
+
+impl Registry {
+    pub fn empty() -> Self {
+        Registry {
+            problems: BTreeMap::new(),
+        }
+    }
+
+    /// Adds a problem, replacing any earlier one with the same name.
+    pub fn register(&mut self, name: impl Into<String>, objective: Objective, runner: Runner) {
+        self.problems.insert(name.into(), (objective, runner));
+    }
+
+    pub fn get(&self, name: &str) -> Option<Runner> {
+        self.problems.get(name).map(|(_, runner)| *runner)
+    }
+
+    /// The objective that experiments with this problem must use.
+    pub fn objective(&self, name: &str) -> Option<Objective> {
+        self.problems.get(name).map(|(objective, _)| *objective)
+    }
+
+    /// Registered names in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.problems.keys().map(|k| k.as_str())
+    }
+
+    /// Runs `config` with its problem. Its population config must use the
+    /// problem's objective; resumed experiments use the checkpoint's config
+    /// and are not checked.
+    pub fn run(&self, config: &ExperimentConfig) -> Result<ExperimentReport, ExperimentError> {
+        let (objective, runner) =
+            self.problems
+                .get(&config.problem)
+                .ok_or_else(|| ExperimentError::UnknownProblem {
+                    name: config.problem.clone(),
+                    known: self.names().map(String::from).collect(),
+                })?;
+        if config.resume.is_none() && config.population.objective != *objective {
+            return Err(ExperimentError::Objective {
+                problem: config.problem.clone(),
+                expected: *objective,
+            });
+        }
+        runner(config)
+    }
+}
+
+/// Runs `config` with the genome `T` and writes the history and checkpoint
+/// files it asks for. Checkpoints record the problem and experiment names in
+/// their metadata, under `problem` and `experiment`. Niching, crowding and
+/// diversity use the genome's `Distance`. Gene entropy is not measured, so
+/// conditions on it are refused. With the `parallel` feature, members are
+/// evaluated across threads.
+pub fn run_problem<T>(config: &ExperimentConfig) -> Result<ExperimentReport, ExperimentError>
+where
+    T: Generate
+        + Crossover
+        + Mutate
+        + Fitness
+        + FitnessRetrieve<Value = f64>
+        + Distance
+        + Default
+        + Clone
+        + Send
+        + Debug
+        + Serialize
+        + DeserializeOwned
+        + 'static,
+{
+    let mut population: Population<T> = match &config.resume {
+        Some(path) => Population::load_checkpoint(path, CheckpointFormat::from_path(path))?,
+        None => Population::try_new(config.population_config())?,
+    };
+    population.use_genome_distance();
+    #[cfg(feature = "parallel")]
+    population.use_parallel_evaluation();
+    let logging = &config.logging;
+    if logging.every > 0 {
+        population.add_observer(ProgressLogger::new(logging.every));
+    }
+    if logging.history_csv.is_some() || logging.history_json.is_some() {
+        population.enable_history();
+    }
+    if logging.diversity {
+        population.enable_diversity();
+    }
+
+    let report = population.run(&config.termination)?;
+
+    if let Some(history) = population.get_history() {
+        if let Some(path) = &logging.history_csv {
+            fs::write(path, history.to_csv())?;
+        }
+        if let Some(path) = &logging.history_json {
+            fs::write(path, history.to_json()?)?;
+        }
+    }
+    if let Some(path) = &logging.checkpoint {
+        let metadata = BTreeMap::from([
+            ("problem".to_string(), config.problem.clone()),
+            ("experiment".to_string(), config.name.clone()),
+        ]);
+        population.save_checkpoint(path, CheckpointFormat::from_path(path), metadata)?;
+    }
+    Ok(ExperimentReport {
+        name: config.name.clone(),
+        problem: config.problem.clone(),
+        reason: report.reason,
+        generations: report.generations,
+        evaluations: report.evaluations,
+        best_fitness: report.best.get_fitness(),
+        best: format!("{:?}", report.best),
+    })
+}
+
+#[derive(Debug)]
+pub enum ExperimentError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    #[cfg(feature = "toml")]
+    Toml(toml::de::Error),
+    #[cfg(feature = "yaml")]
+    Yaml(serde_yaml::Error),
+    /// The file extension is unknown, or its feature is not enabled.
+    UnsupportedFormat(PathBuf),
+    UnknownProblem {
+        name: String,
+        known: Vec<String>,
+    },
+    /// The population config's objective is not the one the problem needs.
+    Objective {
+        problem: String,
+        expected: Objective,
+    },
+    Config(ConfigError),
+    Checkpoint(CheckpointError),
+}
+
+impl fmt::Display for ExperimentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExperimentError::Io(e) => write!(f, "experiment I/O error: {}", e),
+            ExperimentError::Json(e) => write!(f, "invalid JSON experiment: {}", e),
+            #[cfg(feature = "toml")]
+            ExperimentError::Toml(e) => write!(f, "invalid TOML experiment: {}", e),
+            #[cfg(feature = "yaml")]
+            ExperimentError::Yaml(e) => write!(f, "invalid YAML experiment: {}", e),
+            ExperimentError::UnsupportedFormat(path) => {
+                write!(f, "unsupported experiment file format: {}", path.display())
+            }
+            ExperimentError::UnknownProblem { name, known } => write!(
+                f,
+                "unknown problem {:?} (known problems: {})",
+                name,
+                known.join(", ")
+            ),
+            ExperimentError::Objective { problem, expected } => write!(
+                f,
+                "problem {:?} needs population objective {:?}",
+                problem, expected
+            ),
+            ExperimentError::Config(e) => write!(f, "invalid population config: {}", e),
+            ExperimentError::Checkpoint(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ExperimentError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ExperimentError::Io(e) => Some(e),
+            ExperimentError::Json(e) => Some(e),
+            #[cfg(feature = "toml")]
+            ExperimentError::Toml(e) => Some(e),
+            #[cfg(feature = "yaml")]
+            ExperimentError::Yaml(e) => Some(e),
+            ExperimentError::Config(e) => Some(e),
+            ExperimentError::Checkpoint(e) => Some(e),
+            ExperimentError::UnsupportedFormat(_)
+            | ExperimentError::UnknownProblem { .. }
+            | ExperimentError::Objective { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for ExperimentError {
+    fn from(e: io::Error) -> Self {
+        ExperimentError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ExperimentError {
+    fn from(e: serde_json::Error) -> Self {
+        ExperimentError::Json(e)
+    }
+}
+
+#[cfg(feature = "toml")]
+impl From<toml::de::Error> for ExperimentError {
+    fn from(e: toml::de::Error) -> Self {
+        ExperimentError::Toml(e)
+    }
+}
+
+#[cfg(feature = "yaml")]
+impl From<serde_yaml::Error> for ExperimentError {
+    fn from(e: serde_yaml::Error) -> Self {
+        ExperimentError::Yaml(e)
+    }
+}
+
+impl From<ConfigError> for ExperimentError {
+    fn from(e: ConfigError) -> Self {
+        ExperimentError::Config(e)
+    }
+}
+
+impl From<CheckpointError> for ExperimentError {
+    fn from(e: CheckpointError) -> Self {
+        ExperimentError::Checkpoint(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExperimentConfig, ExperimentError, Registry};
+    use crate::{
+        checkpoint::{read_metadata, CheckpointFormat, TempPath},
+        diversity::DiversityMetric,
+        population::ConfigError,
+        termination::Termination,
+    };
+
+    const ONEMAX: &str = r#"{
+        "name": "small",
+        "problem": "onemax",
+        "seed": 3,
+        "termination": { "Any": [{ "MaxGenerations": 30 }, { "TargetFitness": 64.0 }] },
+        "population": {
+            "pop_size": 20,
+            "crossover_count": 8,
+            "mutate_count": 8,
+            "elitism_count": 2,
+            "selection": { "Tournament": { "size": 3 } },
+            "mutation_config": { "gene_mutation_chance": 0.02 }
+        }
+    }"#;
+
+    #[test]
+    fn test_run_by_name() {
+        let experiments = ExperimentConfig::from_json(ONEMAX).unwrap();
+        let registry = Registry::default();
+        let first = registry.run(&experiments[0]).unwrap();
+        assert_eq!("small", first.name);
+        assert!(first.best_fitness.unwrap() > 32.0);
+        // The seed makes runs repeatable.
+        let second = registry.run(&experiments[0]).unwrap();
+        assert_eq!(first.best, second.best);
+
+        let mut unknown = experiments[0].clone();
+        unknown.problem = "missing".to_string();
+        match registry.run(&unknown) {
+            Err(ExperimentError::UnknownProblem { known, .. }) => {
+                assert!(known.contains(&"sphere".to_string()))
+            }
+            other => panic!("expected an unknown problem, got {:?}", other),
+        }
+
+        let mut invalid = experiments[0].clone();
+        invalid.population.elitism_count = 10;
+        assert!(matches!(
+            registry.run(&invalid),
+            Err(ExperimentError::Config(_))
+        ));
+        let mut entropy = experiments[0].clone();
+        entropy.termination = Termination::LowDiversity {
+            metric: DiversityMetric::GeneEntropy,
+            threshold: 0.5,
+        };
+        assert!(matches!(
+            registry.run(&entropy),
+            Err(ExperimentError::Config(ConfigError::UnmeasuredDiversity(
+                DiversityMetric::GeneEntropy
+            )))
+        ));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml_sweep() {
+        use crate::objective::Objective;
+
+        let experiments = ExperimentConfig::from_toml(
+            r#"
+            [[experiments]]
+            problem = "sphere"
+            termination = { MaxGenerations = 5 }
+            population = { pop_size = 10, crossover_count = 4, mutate_count = 4, elitism_count = 1, objective = "Minimize" }
+
+            [[experiments]]
+            problem = "rastrigin"
+            termination = { MaxEvaluations = 50 }
+            population = { pop_size = 10, crossover_count = 4, mutate_count = 4, elitism_count = 1, selection = "Rank" }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(2, experiments.len());
+        let report = Registry::default().run(&experiments[0]).unwrap();
+        assert_eq!(Termination::MaxGenerations(5), report.reason);
+        match Registry::default().run(&experiments[1]) {
+            Err(ExperimentError::Objective { problem, expected }) => {
+                assert_eq!(
+                    ("rastrigin", Objective::Minimize),
+                    (problem.as_str(), expected)
+                )
+            }
+            other => panic!("expected an objective error, got {:?}", other),
+        }
+
+        let error = ExperimentConfig::from_toml("problem = \"sphere\"\n").unwrap_err();
+        assert!(error.to_string().contains("population"), "{}", error);
+    }
+
+    #[test]
+    fn test_resume() {
+        let path = TempPath::new("experiment_checkpoint.json");
+        let mut config = ExperimentConfig::from_json(ONEMAX).unwrap().remove(0);
+        config.termination = Termination::MaxGenerations(5);
+        config.logging.checkpoint = Some(path.to_path_buf());
+        let registry = Registry::default();
+        let first = registry.run(&config).unwrap();
+        assert_eq!(5, first.generations);
+
+        let metadata = read_metadata(&path, CheckpointFormat::Json).unwrap();
+        assert_eq!("onemax", metadata["problem"]);
+        config.termination = Termination::MaxGenerations(8);
+        config.logging.checkpoint = None;
+        config.resume = Some(path.to_path_buf());
+        let resumed = registry.run(&config).unwrap();
+        assert_eq!(8, resumed.generations);
+        assert!(resumed.evaluations > first.evaluations);
+    }
+
+    #[cfg(all(feature = "toml", feature = "yaml"))]
+    #[test]
+    fn test_example_files() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../examples/experiments");
+        let onemax = ExperimentConfig::load_all(dir.join("onemax.toml")).unwrap();
+        assert_eq!(Some(7), onemax[0].seed);
+        let sweep = ExperimentConfig::load_all(dir.join("sphere_sweep.yaml")).unwrap();
+        let chances: Vec<f64> = sweep
+            .iter()
+            .map(|e| e.population.mutation_config.gene_mutation_chance)
+            .collect();
+        assert_eq!(vec![0.05, 0.2, 0.5], chances);
+        assert_eq!(40, sweep[2].population.pop_size);
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_yaml() {
+        let experiments = ExperimentConfig::from_yaml(
+            "
+problem: onemax
+termination: !MaxGenerations 3
+population:
+  pop_size: 8
+  crossover_count: 2
+  mutate_count: 2
+  elitism_count: 1
+  crossover_config:
+    array: !Uniform
+      bias: 0.5
+",
+        )
+        .unwrap();
+        let report = Registry::default().run(&experiments[0]).unwrap();
+        assert_eq!(3, report.generations);
+    }
+}
@@ -0,0 +1,666 @@
+use std::marker::PhantomData;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    population::{probability, CrossoverConfig, Genome, MutationConfig},
+    traits::{Crossover, Distance, FitnessRetrieve, FitnessValue, Generate, Mutate},
+};
+
+pub type PrimitiveFn = fn(&[f64]) -> f64;
+pub type EphemeralFn = fn(&mut StdRng) -> f64;
+
+#[derive(Debug, Clone)]
+pub struct Function {
+    pub name: String,
+    pub arity: usize,
+    pub apply: PrimitiveFn,
+}
+
+/// A constant whose value is drawn once, when the node is created.
+#[derive(Debug, Clone)]
+pub struct Ephemeral {
+    pub name: String,
+    pub generate: EphemeralFn,
+}
+
+/// The functions and leaves trees are built from.
+///
+/// ```
+/// use ga::gp::PrimitiveSet;
+/// use rand::Rng;
+///
+/// let set = PrimitiveSet::new()
+///     .function("+", 2, |a| a[0] + a[1])
+///     .function("neg", 1, |a| -a[0])
+///     .terminal("pi", std::f64::consts::PI)
+///     .ephemeral("int", |rng| rng.gen_range(-10..=10) as f64)
+///     .variable("x");
+/// ```
+#[derive(Debug, Clone)]
+pub struct PrimitiveSet {
+    pub functions: Vec<Function>,
+    pub terminals: Vec<(String, f64)>,
+    pub ephemerals: Vec<Ephemeral>,
+    pub variables: Vec<String>,
+    /// Depth range used when generating new trees.
+    pub init_depth: (usize, usize),
+    /// Offspring deeper than this are rejected in favour of a copy of the parent.
+    pub max_depth: usize,
+}
+
+impl Default for PrimitiveSet {
+    fn default() -> Self {
+        PrimitiveSet {
+            functions: Vec::new(),
+            terminals: Vec::new(),
+            ephemerals: Vec::new(),
+            variables: Vec::new(),
+            init_depth: (2, 5),
+            max_depth: 8,
+        }
+    }
+}
+
+impl PrimitiveSet {
+    pub fn new() -> Self {
+        PrimitiveSet::default()
+    }
+    pub fn function(mut self, name: &str, arity: usize, apply: PrimitiveFn) -> Self {
+        self.functions.push(Function {
+            name: name.to_string(),
+            arity,
+            apply,
+        });
+        self
+    }
+    pub fn terminal(mut self, name: &str, value: f64) -> Self {
+        self.terminals.push((name.to_string(), value));
+        self
+    }
+    pub fn ephemeral(mut self, name: &str, generate: EphemeralFn) -> Self {
+        self.ephemerals.push(Ephemeral {
+            name: name.to_string(),
+            generate,
+        });
+        self
+    }
+    pub fn variable(mut self, name: &str) -> Self {
+        self.variables.push(name.to_string());
+        self
+    }
+    pub fn init_depth(mut self, min: usize, max: usize) -> Self {
+        self.init_depth = (min, max);
+        self
+    }
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    fn leaf_count(&self) -> usize {
+        self.terminals.len() + self.ephemerals.len() + self.variables.len()
+    }
+
+    pub fn random_leaf(&self, rng: &mut StdRng) -> Node {
+        let count = self.leaf_count();
+        if count == 0 {
+            return Node::Constant(0.0);
+        }
+        let mut i = rng.gen_range(0..count);
+        if i < self.terminals.len() {
+            return Node::Terminal(i);
+        }
+        i -= self.terminals.len();
+        if i < self.ephemerals.len() {
+            return Node::Constant((self.ephemerals[i].generate)(rng));
+        }
+        Node::Variable(i - self.ephemerals.len())
+    }
+
+    fn random_function(&self, depth: usize, full: bool, rng: &mut StdRng) -> Node {
+        let index = rng.gen_range(0..self.functions.len());
+        let children = (0..self.functions[index].arity)
+            .map(|_| {
+                if full {
+                    self.full(depth - 1, rng)
+                } else {
+                    self.grow(depth - 1, rng)
+                }
+            })
+            .collect();
+        Node::Function { index, children }
+    }
+
+    /// A tree where every leaf sits at exactly `depth`.
+    pub fn full(&self, depth: usize, rng: &mut StdRng) -> Node {
+        if depth == 0 || self.functions.is_empty() {
+            self.random_leaf(rng)
+        } else {
+            self.random_function(depth, true, rng)
+        }
+    }
+
+    /// A tree of depth at most `depth`, picking freely between functions and leaves.
+    pub fn grow(&self, depth: usize, rng: &mut StdRng) -> Node {
+        let total = self.functions.len() + self.leaf_count();
+        if depth == 0
+            || self.functions.is_empty()
+            || rng.gen_range(0..total) >= self.functions.len()
+        {
+            self.random_leaf(rng)
+        } else {
+            self.random_function(depth, false, rng)
+        }
+    }
+
+    /// Ramped half-and-half: depths are spread evenly over `init_depth` and
+    /// half of the trees at each depth are built with `full`, half with `grow`.
+    pub fn ramped_half_and_half(&self, count: usize, seed: [u8; 32]) -> Vec<Node> {
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let (min, max) = self.init_depth;
+        let depths = max.saturating_sub(min) + 1;
+        (0..count)
+            .map(|i| {
+                let depth = min + i % depths;
+                if (i / depths) % 2 == 0 {
+                    self.full(depth, &mut rng)
+                } else {
+                    self.grow(depth, &mut rng)
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Node {
+    Function { index: usize, children: Vec<Node> },
+    Terminal(usize),
+    Constant(f64),
+    Variable(usize),
+}
+
+impl Default for Node {
+    fn default() -> Self {
+        Node::Constant(0.0)
+    }
+}
+
+impl Node {
+    pub fn evaluate(&self, set: &PrimitiveSet, variables: &[f64]) -> f64 {
+        match self {
+            Node::Function { index, children } => {
+                let args: Vec<f64> = children
+                    .iter()
+                    .map(|c| c.evaluate(set, variables))
+                    .collect();
+                (set.functions[*index].apply)(&args)
+            }
+            Node::Terminal(i) => set.terminals[*i].1,
+            Node::Constant(v) => *v,
+            Node::Variable(i) => variables.get(*i).copied().unwrap_or(0.0),
+        }
+    }
+
+    /// Binary functions are printed infix, everything else as `name(args)`.
+    pub fn print(&self, set: &PrimitiveSet) -> String {
+        match self {
+            Node::Function { index, children } => {
+                let name = &set.functions[*index].name;
+                let args: Vec<String> = children.iter().map(|c| c.print(set)).collect();
+                if args.len() == 2 {
+                    format!("({}{}{})", args[0], name, args[1])
+                } else {
+                    format!("{}({})", name, args.join(", "))
+                }
+            }
+            Node::Terminal(i) => set.terminals[*i].0.clone(),
+            Node::Constant(v) => v.to_string(),
+            Node::Variable(i) => set.variables[*i].clone(),
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        match self {
+            Node::Function { children, .. } => {
+                1 + children.iter().map(|c| c.node_count()).sum::<usize>()
+            }
+            _ => 1,
+        }
+    }
+
+    /// Leaves have depth 0.
+    pub fn depth(&self) -> usize {
+        match self {
+            Node::Function { children, .. } => {
+                1 + children.iter().map(|c| c.depth()).max().unwrap_or(0)
+            }
+            _ => 0,
+        }
+    }
+
+    /// Nodes outside the region the two trees have in common. Overlaying the
+    /// trees from the root, functions match if they are the same with the
+    /// same arity, and leaves if they are equal; any other pair of subtrees
+    /// counts all of their nodes.
+    pub fn distance(&self, other: &Node) -> usize {
+        match (self, other) {
+            (
+                Node::Function { index, children },
+                Node::Function {
+                    index: other_index,
+                    children: other_children,
+                },
+            ) if index == other_index && children.len() == other_children.len() => children
+                .iter()
+                .zip(other_children)
+                .map(|(a, b)| a.distance(b))
+                .sum(),
+            (a, b) if a.is_leaf() && a == b => 0,
+            (a, b) => a.node_count() + b.node_count(),
+        }
+    }
+
+    pub fn is_leaf(&self) -> bool {
+        !matches!(self, Node::Function { .. })
+    }
+
+    /// The `n`th node in pre-order, counting from 0 at `self`.
+    pub fn get_nth_node(&self, n: usize) -> Option<&Node> {
+        if n == 0 {
+            return Some(self);
+        }
+        let mut remaining = n - 1;
+        if let Node::Function { children, .. } = self {
+            for child in children {
+                let count = child.node_count();
+                if remaining < count {
+                    return child.get_nth_node(remaining);
+                }
+                remaining -= count;
+            }
+        }
+        None
+    }
+
+    /// A copy of `self` with the `n`th pre-order node swapped for `replacement`.
+    pub fn replace_nth_node(&self, n: usize, replacement: &Node) -> Node {
+        if n == 0 {
+            return replacement.clone();
+        }
+        match self {
+            Node::Function { index, children } => {
+                let mut remaining = n - 1;
+                let children = children
+                    .iter()
+                    .map(|child| {
+                        let count = child.node_count();
+                        let replaced = if remaining < count {
+                            child.replace_nth_node(remaining, replacement)
+                        } else {
+                            child.clone()
+                        };
+                        remaining = remaining.wrapping_sub(count);
+                        replaced
+                    })
+                    .collect();
+                Node::Function {
+                    index: *index,
+                    children,
+                }
+            }
+            leaf => leaf.clone(),
+        }
+    }
+
+    /// How far below the root the `n`th pre-order node sits.
+    pub fn level_of_nth_node(&self, n: usize) -> usize {
+        if n == 0 {
+            return 0;
+        }
+        let mut remaining = n - 1;
+        if let Node::Function { children, .. } = self {
+            for child in children {
+                let count = child.node_count();
+                if remaining < count {
+                    return 1 + child.level_of_nth_node(remaining);
+                }
+                remaining -= count;
+            }
+        }
+        0
+    }
+}
+
+/// Mutation operators for trees.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TreeMutation {
+    /// Swaps each node, with `gene_mutation_chance`, for a primitive of the same arity.
+    Point,
+    /// Replaces a random subtree with a freshly grown one.
+    #[default]
+    Subtree,
+    /// Promotes a random subtree to be the whole tree.
+    Hoist,
+    /// Replaces a random function node with a leaf.
+    Shrink,
+}
+
+/// Supplies the primitive set for a family of trees.
+///
+/// ```
+/// use std::sync::OnceLock;
+/// use ga::gp::{PrimitiveSet, Primitives};
+///
+/// struct Arithmetic;
+///
+/// impl Primitives for Arithmetic {
+///     fn primitive_set() -> &'static PrimitiveSet {
+///         static SET: OnceLock<PrimitiveSet> = OnceLock::new();
+///         SET.get_or_init(|| {
+///             PrimitiveSet::new()
+///                 .function("+", 2, |a| a[0] + a[1])
+///                 .variable("x")
+///         })
+///     }
+/// }
+/// ```
+pub trait Primitives {
+    fn primitive_set() -> &'static PrimitiveSet;
+}
+
+/// An expression tree genome built from the primitives of `P`.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "F: FitnessValue")]
+pub struct Tree<P: Primitives, F = f64> {
+    inner: Genome<Node, F>,
+    #[serde(skip)]
+    primitives: PhantomData<P>,
+}
+
+impl<P: Primitives, F: FitnessValue> Tree<P, F> {
+    pub fn new(root: Node) -> Self {
+        Tree {
+            inner: Genome {
+                data: root,
+                ..Default::default()
+            },
+            primitives: PhantomData,
+        }
+    }
+    pub fn get_root(&self) -> &Node {
+        &self.inner.data
+    }
+    /// Number of times a fitness has been set on this genome.
+    pub fn get_evaluations(&self) -> u64 {
+        self.inner.evaluations
+    }
+    pub fn evaluate(&self, variables: &[f64]) -> f64 {
+        self.inner.data.evaluate(P::primitive_set(), variables)
+    }
+    pub fn print(&self) -> String {
+        self.inner.data.print(P::primitive_set())
+    }
+    /// Keeps `root` if it fits within `max_depth`, otherwise falls back to a copy of `self`.
+    fn offspring(&self, root: Node) -> Self {
+        if root.depth() <= P::primitive_set().max_depth {
+            Tree::new(root)
+        } else {
+            Tree::new(self.inner.data.clone())
+        }
+    }
+}
+
+impl<P: Primitives, F: FitnessValue> Clone for Tree<P, F> {
+    fn clone(&self) -> Self {
+        Tree {
+            inner: self.inner.clone(),
+            primitives: PhantomData,
+        }
+    }
+}
+
+impl<P: Primitives, F: FitnessValue> Default for Tree<P, F> {
+    fn default() -> Self {
+        Tree::new(Node::default())
+    }
+}
+
+impl<P: Primitives, F: FitnessValue> std::fmt::Debug for Tree<P, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tree").field("inner", &self.inner).finish()
+    }
+}
+
+impl<P: Primitives, F: FitnessValue> Generate for Tree<P, F> {
+    /// Picks a depth from `init_depth` and, with equal odds, the full or grow
+    /// method, which is ramped half-and-half for a single tree.
+    fn generate(seed: [u8; 32]) -> Self {
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let set = P::primitive_set();
+        let (min, max) = set.init_depth;
+        let depth = rng.gen_range(min..=max.max(min));
+        let root = if rng.gen_bool(0.5) {
+            set.full(depth, &mut rng)
+        } else {
+            set.grow(depth, &mut rng)
+        };
+        Tree::new(root)
+    }
+}
+
+impl<P: Primitives, F: FitnessValue> Mutate for Tree<P, F> {
+    fn mutate(&self, config: &MutationConfig, seed: [u8; 32]) -> Self {
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let set = P::primitive_set();
+        let root = &self.inner.data;
+        let n = rng.gen_range(0..root.node_count());
+
+        let mutated = match config.tree {
+            TreeMutation::Point => point_mutation(root, set, config.gene_mutation_chance, &mut rng),
+            TreeMutation::Subtree => {
+                let room = set.max_depth.saturating_sub(root.level_of_nth_node(n));
+                let depth = rng.gen_range(0..=room.min(set.init_depth.1));
+                root.replace_nth_node(n, &set.grow(depth, &mut rng))
+            }
+            TreeMutation::Hoist => root.get_nth_node(n).cloned().unwrap_or_default(),
+            TreeMutation::Shrink => {
+                let functions: Vec<usize> = (0..root.node_count())
+                    .filter(|i| root.get_nth_node(*i).is_some_and(|node| !node.is_leaf()))
+                    .collect();
+                if functions.is_empty() {
+                    root.clone()
+                } else {
+                    let n = functions[rng.gen_range(0..functions.len())];
+                    root.replace_nth_node(n, &set.random_leaf(&mut rng))
+                }
+            }
+        };
+        self.offspring(mutated)
+    }
+}
+
+fn point_mutation(node: &Node, set: &PrimitiveSet, chance: f64, rng: &mut StdRng) -> Node {
+    let mutate = rng.gen_bool(probability(chance));
+    match node {
+        Node::Function { index, children } => {
+            let arity = children.len();
+            let index = if mutate {
+                let same_arity: Vec<usize> = (0..set.functions.len())
+                    .filter(|i| set.functions[*i].arity == arity)
+                    .collect();
+                same_arity[rng.gen_range(0..same_arity.len())]
+            } else {
+                *index
+            };
+            Node::Function {
+                index,
+                children: children
+                    .iter()
+                    .map(|c| point_mutation(c, set, chance, rng))
+                    .collect(),
+            }
+        }
+        _ if mutate => set.random_leaf(rng),
+        leaf => leaf.clone(),
+    }
+}
+
+impl<P: Primitives, F: FitnessValue> Crossover for Tree<P, F> {
+    /// Subtree crossover: a random subtree of `self` is replaced by a random subtree of `other`.
+    fn crossover(&self, other: &Self, _config: &CrossoverConfig, seed: [u8; 32]) -> Self {
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let root = &self.inner.data;
+        let donor = &other.inner.data;
+
+        let n = rng.gen_range(0..root.node_count());
+        let subtree = donor
+            .get_nth_node(rng.gen_range(0..donor.node_count()))
+            .cloned()
+            .unwrap_or_default();
+        self.offspring(root.replace_nth_node(n, &subtree))
+    }
+}
+
+impl<P: Primitives, F: FitnessValue> FitnessRetrieve for Tree<P, F> {
+    type Value = F;
+    fn get_fitness(&self) -> Option<F> {
+        self.inner.fitness.clone()
+    }
+    fn set_fitness(&mut self, fitness: Option<F>) {
+        self.inner.set_fitness(fitness);
+    }
+}
+
+/// See `Node::distance`.
+impl<P: Primitives, F: FitnessValue> Distance for Tree<P, F> {
+    fn distance(&self, other: &Self) -> f64 {
+        self.inner.data.distance(&other.inner.data) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::OnceLock;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::{Node, PrimitiveSet, Primitives, Tree, TreeMutation};
+    use crate::{
+        population::{CrossoverConfig, MutationConfig},
+        traits::{Crossover, FitnessRetrieve, Generate, Mutate},
+    };
+
+    struct Arithmetic;
+
+    impl Primitives for Arithmetic {
+        fn primitive_set() -> &'static PrimitiveSet {
+            static SET: OnceLock<PrimitiveSet> = OnceLock::new();
+            SET.get_or_init(|| {
+                PrimitiveSet::new()
+                    .function("+", 2, |a| a[0] + a[1])
+                    .function("*", 2, |a| a[0] * a[1])
+                    .function("neg", 1, |a| -a[0])
+                    .terminal("one", 1.0)
+                    .ephemeral("small", |rng| rng.gen_range(0..10) as f64)
+                    .variable("x")
+                    .init_depth(1, 4)
+                    .max_depth(6)
+            })
+        }
+    }
+
+    #[test]
+    fn test_evaluate_and_print() {
+        let set = Arithmetic::primitive_set();
+        // (x * (1 + 3)) + neg(x)
+        let root = Node::Function {
+            index: 0,
+            children: vec![
+                Node::Function {
+                    index: 1,
+                    children: vec![
+                        Node::Variable(0),
+                        Node::Function {
+                            index: 0,
+                            children: vec![Node::Terminal(0), Node::Constant(3.0)],
+                        },
+                    ],
+                },
+                Node::Function {
+                    index: 2,
+                    children: vec![Node::Variable(0)],
+                },
+            ],
+        };
+        assert_eq!(15.0, root.evaluate(set, &[5.0]));
+        assert_eq!("((x*(one+3))+neg(x))", root.print(set));
+        assert_eq!(8, root.node_count());
+        assert_eq!(3, root.depth());
+        assert_eq!(Some(&Node::Terminal(0)), root.get_nth_node(4));
+        assert_eq!(3, root.level_of_nth_node(4));
+        assert_eq!(
+            Some(&Node::Variable(0)),
+            root.replace_nth_node(2, &Node::Variable(0)).get_nth_node(2)
+        );
+        assert_eq!(root, root.replace_nth_node(7, &Node::Variable(0)));
+        assert_eq!(0, root.distance(&root));
+        // Swapping 1 + 3 for x leaves the rest of the tree in common.
+        assert_eq!(
+            4,
+            root.distance(&root.replace_nth_node(3, &Node::Variable(0)))
+        );
+        assert_eq!(9, root.distance(&Node::Variable(0)));
+    }
+
+    #[test]
+    fn test_ramped_half_and_half() {
+        let set = Arithmetic::primitive_set();
+        let trees = set.ramped_half_and_half(16, [2; 32]);
+        trees
+            .iter()
+            .for_each(|t| assert!(t.depth() <= set.init_depth.1));
+        // Trees built with `full` reach exactly the ramped depth.
+        assert_eq!(1, trees[0].depth());
+        assert_eq!(4, trees[3].depth());
+        assert_eq!(4, trees[11].depth());
+    }
+
+    #[test]
+    fn test_operators_respect_max_depth() {
+        let mutations = [
+            TreeMutation::Point,
+            TreeMutation::Subtree,
+            TreeMutation::Hoist,
+            TreeMutation::Shrink,
+        ];
+        let mut rng: StdRng = SeedableRng::from_seed([4; 32]);
+        let mut trees: Vec<Tree<Arithmetic>> = (0..10).map(|_| Tree::generate(rng.gen())).collect();
+        for _ in 0..50 {
+            let a = &trees[rng.gen_range(0..trees.len())];
+            let b = &trees[rng.gen_range(0..trees.len())];
+            let crossed = a.crossover(b, &CrossoverConfig::default(), rng.gen());
+            let config = MutationConfig {
+                gene_mutation_chance: 0.2,
+                tree: mutations[rng.gen_range(0..mutations.len())].clone(),
+                ..Default::default()
+            };
+            let mutated = crossed.mutate(&config, rng.gen());
+            assert!(mutated.get_root().depth() <= 6);
+            trees.push(mutated);
+        }
+    }
+
+    #[test]
+    fn test_serialize_keeps_fitness() {
+        let mut tree: Tree<Arithmetic> = Tree::generate([8; 32]);
+        tree.set_fitness(Some(-3.0));
+        let json = serde_json::to_string(&tree).unwrap();
+        let loaded: Tree<Arithmetic> = serde_json::from_str(&json).unwrap();
+        assert_eq!(tree.get_root(), loaded.get_root());
+        assert_eq!(Some(-3.0), loaded.get_fitness());
+        assert_eq!(1, loaded.get_evaluations());
+    }
+}
@@ -1,24 +1,26 @@
 use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    population::{Genome, MutationConfig},
-    traits::{Crossover, FitnessRetrieve, Generate, Mutate},
+    population::{CrossoverConfig, Genome, MutationConfig},
+    traits::{Crossover, Distance, FitnessRetrieve, FitnessValue, Generate, Genes, Mutate},
 };
 
 pub const DEFAULT_MIN_LEN: usize = 20;
 pub const DEFAULT_MAX_LEN: usize = 20;
 
-#[derive(Default, Clone, Debug)]
-pub struct ItemArray<T: Clone + Default + Mutate> {
-    inner: Genome<Vec<T>>,
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct ItemArray<T: Clone + Default + Mutate, F = f64> {
+    inner: Genome<Vec<T>, F>,
 }
 
-impl<T: Clone + Default + Mutate + Generate> ItemArray<T> {
+impl<T: Clone + Default + Mutate + Generate, F: FitnessValue> ItemArray<T, F> {
     pub fn get_data(&self) -> &Vec<T> {
         &self.inner.data
     }
-    pub fn set_fitness(&mut self, fitness: Option<f64>) {
-        self.inner.fitness = fitness;
+    /// Number of times a fitness has been set on this genome.
+    pub fn get_evaluations(&self) -> u64 {
+        self.inner.evaluations
     }
     pub fn generate_length(min_length: usize, max_length: usize, seed: [u8; 32]) -> Self {
         let mut rng: StdRng = SeedableRng::from_seed(seed);
@@ -34,7 +36,7 @@ impl<T: Clone + Default + Mutate + Generate> ItemArray<T> {
     }
 }
 
-impl<T: Clone + Default + Mutate> Mutate for ItemArray<T> {
+impl<T: Clone + Default + Mutate, F: FitnessValue> Mutate for ItemArray<T, F> {
     fn mutate(&self, config: &MutationConfig, seed: [u8; 32]) -> Self {
         let mut rng: StdRng = SeedableRng::from_seed(seed);
         let new_data = self
@@ -58,29 +60,20 @@ impl<T: Clone + Default + Mutate> Mutate for ItemArray<T> {
     }
 }
 
-impl<T: Clone + Default + Mutate> Crossover for ItemArray<T> {
-    fn crossover(&self, other: &Self, seed: [u8; 32]) -> Self {
-        let mut rng: StdRng = SeedableRng::from_seed(seed);
-        let min_length = std::cmp::min(self.inner.data.len(), other.inner.data.len());
-
-        let crossover_point = rng.gen_range(0..=min_length);
-
-        let mut offspring =
-            Vec::with_capacity(std::cmp::max(self.inner.data.len(), other.inner.data.len()));
-
-        offspring.extend_from_slice(&self.inner.data[..crossover_point]);
-        offspring.extend_from_slice(&other.inner.data[crossover_point..]);
-
+impl<T: Clone + Default + Mutate, F: FitnessValue> Crossover for ItemArray<T, F> {
+    fn crossover(&self, other: &Self, config: &CrossoverConfig, seed: [u8; 32]) -> Self {
         ItemArray {
             inner: Genome {
-                data: offspring,
+                data: config
+                    .array
+                    .apply(&self.inner.data, &other.inner.data, seed),
                 ..Default::default()
             },
         }
     }
 }
 
-impl<T: Clone + Generate + Default + Mutate> Generate for ItemArray<T> {
+impl<T: Clone + Generate + Default + Mutate, F: FitnessValue> Generate for ItemArray<T, F> {
     fn generate(seed: [u8; 32]) -> Self {
         let mut rng: StdRng = SeedableRng::from_seed(seed);
 
@@ -95,8 +88,29 @@ impl<T: Clone + Generate + Default + Mutate> Generate for ItemArray<T> {
     }
 }
 
-impl<T: Copy + Default + Mutate> FitnessRetrieve for ItemArray<T> {
-    fn get_fitness(&self) -> Option<f64> {
-        self.inner.fitness
+impl<T: Copy + Default + Mutate, F: FitnessValue> FitnessRetrieve for ItemArray<T, F> {
+    type Value = F;
+    fn get_fitness(&self) -> Option<F> {
+        self.inner.fitness.clone()
+    }
+    fn set_fitness(&mut self, fitness: Option<F>) {
+        self.inner.set_fitness(fitness);
+    }
+}
+
+/// Hamming distance: the positions holding different items, plus the
+/// difference in length.
+impl<T: Clone + Default + Mutate + PartialEq, F> Distance for ItemArray<T, F> {
+    fn distance(&self, other: &Self) -> f64 {
+        let (a, b) = (&self.inner.data, &other.inner.data);
+        let differing = a.iter().zip(b).filter(|(x, y)| x != y).count();
+        (differing + a.len().abs_diff(b.len())) as f64
+    }
+}
+
+impl<T: Clone + Default + Mutate + PartialEq, F> Genes for ItemArray<T, F> {
+    type Gene = T;
+    fn genes(&self) -> &[T] {
+        &self.inner.data
     }
 }
@@ -1,3 +1,24 @@
+pub mod archipelago;
+pub mod bit_string;
+pub mod cache;
+pub mod checkpoint;
+pub mod crossover;
+pub mod diversity;
+pub mod experiment;
+pub mod gp;
 pub mod item_array;
+pub mod niching;
+pub mod noise;
+pub mod nsga2;
+pub mod objective;
+pub mod observer;
+pub mod parallel;
+pub mod permutation;
 pub mod population;
+pub mod problems;
+pub mod real_vector;
+pub mod replacement;
+pub mod selection;
+pub mod stats;
+pub mod termination;
 pub mod traits;
@@ -0,0 +1,212 @@
+//! Niching keeps several lineages alive by making selection depend on how
+//! crowded each member's neighbourhood is.
+//!
+//! All methods work on a rescaled fitness where higher is better and the worst
+//! member is at 0; see `goodness`. Tuple fitnesses are rescaled by their
+//! first component only. Stats, elitism and termination still use the
+//! members' own fitness. Distances come from the function given to
+//! `Population::set_distance`, usually a `Distance` impl.
+
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{objective::Objective, traits::FitnessValue};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Niching {
+    /// Divides each member's fitness by its niche count, the sum of
+    /// `1 - (d / sigma)^alpha` over the members within distance `sigma`,
+    /// itself included.
+    Sharing {
+        sigma: f64,
+        #[serde(default = "default_alpha")]
+        alpha: f64,
+    },
+    /// Within `sigma` of each niche's best member, only the best `capacity`
+    /// members keep their fitness; the rest get the worst.
+    Clearing { sigma: f64, capacity: usize },
+    /// Groups members into species: each member joins the first species
+    /// whose representative is within `threshold`, or founds a new one.
+    /// Species are formed again every generation, best members first, and get
+    /// offspring in proportion to their mean fitness. Parents are chosen
+    /// within a species.
+    Speciation { threshold: f64 },
+}
+
+fn default_alpha() -> f64 {
+    1.0
+}
+
+impl Niching {
+    /// False for radii and thresholds that are not positive, and capacities of 0.
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            Niching::Sharing { sigma, alpha } => sigma > 0.0 && alpha > 0.0,
+            Niching::Clearing { sigma, capacity } => sigma > 0.0 && capacity > 0,
+            Niching::Speciation { threshold } => threshold > 0.0,
+        }
+    }
+}
+
+/// Rescales fitnesses so that higher is better and the worst usable one is
+/// 0. Missing, invalid and non-finite fitnesses stay `None`.
+pub fn goodness<F: FitnessValue>(
+    fitnesses: &[Option<F>],
+    objective: Objective,
+) -> Vec<Option<f64>> {
+    let values: Vec<Option<f64>> = fitnesses
+        .iter()
+        .map(|f| {
+            f.as_ref()
+                .filter(|f| f.is_valid())
+                .map(|f| f.to_f64())
+                .filter(|v| v.is_finite())
+        })
+        .collect();
+    let usable = values.iter().flatten().copied();
+    let worst = match objective {
+        Objective::Maximize => usable.reduce(f64::min),
+        Objective::Minimize => usable.reduce(f64::max),
+    };
+    values
+        .iter()
+        .map(|v| Some((v.as_ref()? - worst?).abs()))
+        .collect()
+}
+
+/// Fitness sharing over `goodness`, with `distance` between member indices.
+pub fn share(
+    goodness: &[Option<f64>],
+    distance: impl Fn(usize, usize) -> f64,
+    sigma: f64,
+    alpha: f64,
+) -> Vec<Option<f64>> {
+    let len = goodness.len();
+    (0..len)
+        .map(|i| {
+            let niche_count: f64 = (0..len)
+                .filter(|j| *j != i)
+                .map(|j| distance(i, j))
+                .filter(|d| *d < sigma)
+                .map(|d| 1.0 - (d / sigma).powf(alpha))
+                .sum();
+            Some(goodness[i]? / (1.0 + niche_count))
+        })
+        .collect()
+}
+
+/// Clearing over `goodness`, with `distance` between member indices.
+pub fn clear(
+    goodness: &[Option<f64>],
+    distance: impl Fn(usize, usize) -> f64,
+    sigma: f64,
+    capacity: usize,
+) -> Vec<Option<f64>> {
+    let mut order: Vec<usize> = (0..goodness.len())
+        .filter(|i| goodness[*i].is_some())
+        .collect();
+    order.sort_by(|a, b| {
+        goodness[*b]
+            .partial_cmp(&goodness[*a])
+            .unwrap_or(Ordering::Equal)
+    });
+    let mut cleared = goodness.to_vec();
+    let mut placed = vec![false; goodness.len()];
+    for (n, &winner) in order.iter().enumerate() {
+        if placed[winner] {
+            continue;
+        }
+        placed[winner] = true;
+        let mut winners = 1;
+        for &other in &order[n + 1..] {
+            if placed[other] || distance(winner, other) >= sigma {
+                continue;
+            }
+            placed[other] = true;
+            if winners < capacity {
+                winners += 1;
+            } else {
+                cleared[other] = Some(0.0);
+            }
+        }
+    }
+    cleared
+}
+
+/// Splits `len` members, best first, into species of member indices. The
+/// first member of each species is its representative.
+pub fn speciate(
+    len: usize,
+    distance: impl Fn(usize, usize) -> f64,
+    threshold: f64,
+) -> Vec<Vec<usize>> {
+    let mut species: Vec<Vec<usize>> = Vec::new();
+    for i in 0..len {
+        match species.iter_mut().find(|s| distance(s[0], i) < threshold) {
+            Some(s) => s.push(i),
+            None => species.push(vec![i]),
+        }
+    }
+    species
+}
+
+/// Splits `total` in proportion to `weights` by largest remainder, ties going
+/// to the earlier entry. Splits evenly if no weight is positive.
+pub fn allocate(weights: &[f64], total: usize) -> Vec<usize> {
+    let sum: f64 = weights.iter().sum();
+    let quotas: Vec<f64> = if sum > 0.0 && sum.is_finite() {
+        weights.iter().map(|w| w / sum * total as f64).collect()
+    } else {
+        vec![total as f64 / weights.len() as f64; weights.len()]
+    };
+    let mut counts: Vec<usize> = quotas.iter().map(|q| q.floor() as usize).collect();
+    let mut order: Vec<usize> = (0..quotas.len()).collect();
+    order.sort_by(|a, b| {
+        (quotas[*b] - quotas[*b].floor())
+            .partial_cmp(&(quotas[*a] - quotas[*a].floor()))
+            .unwrap_or(Ordering::Equal)
+    });
+    let remaining = total.saturating_sub(counts.iter().sum());
+    order.iter().take(remaining).for_each(|i| counts[*i] += 1);
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{allocate, clear, goodness, share, speciate};
+    use crate::objective::Objective;
+
+    #[test]
+    fn test_niching() {
+        let fitnesses = [Some(1.0), Some(3.0), None, Some(2.0)];
+        assert_eq!(
+            vec![Some(0.0), Some(2.0), None, Some(1.0)],
+            goodness(&fitnesses, Objective::Maximize)
+        );
+        assert_eq!(
+            vec![Some(2.0), Some(0.0), None, Some(1.0)],
+            goodness(&fitnesses, Objective::Minimize)
+        );
+
+        // Members 0 and 1 sit together, 2 is alone.
+        let positions = [0.0, 0.5, 10.0];
+        let distance = |i: usize, j: usize| f64::abs(positions[i] - positions[j]);
+        let values = [Some(4.0), Some(3.0), Some(1.0)];
+        assert_eq!(
+            vec![Some(4.0 / 1.5), Some(2.0), Some(1.0)],
+            share(&values, distance, 1.0, 1.0)
+        );
+        assert_eq!(
+            vec![Some(4.0), Some(0.0), Some(1.0)],
+            clear(&values, distance, 1.0, 1)
+        );
+        assert_eq!(values.to_vec(), clear(&values, distance, 1.0, 2));
+        assert_eq!(vec![vec![0, 1], vec![2]], speciate(3, distance, 1.0));
+
+        assert_eq!(vec![3, 1], allocate(&[3.0, 1.0], 4));
+        assert_eq!(vec![2, 1, 1], allocate(&[1.0, 1.0, 1.0], 4));
+        assert_eq!(vec![1, 1], allocate(&[0.0, 0.0], 2));
+        assert_eq!(vec![5], allocate(&[0.0], 5));
+    }
+}
@@ -0,0 +1,126 @@
+//! Support for fitness functions that return a different value each time the
+//! same genome is evaluated.
+
+use serde::{Deserialize, Serialize};
+
+/// How a population evaluates noisy fitness functions.
+///
+/// Every evaluation draws `samples` fitness values with their own seeds and
+/// stores their mean on the member through `FitnessRetrieve::set_fitness`.
+/// Fitness types without `FitnessValue::from_f64`, such as tuples, get the
+/// median of each evaluation's samples instead. Members whose samples are all invalid keep
+/// their earlier fitness. The fitness cache is not used while noise handling
+/// is enabled.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoiseConfig {
+    /// Fitness samples drawn per evaluation.
+    pub samples: usize,
+    /// Adds `samples` more samples to every elite each generation, so that
+    /// members kept through luck lose their advantage.
+    #[serde(default)]
+    pub reevaluate_elites: bool,
+    /// Critical value of the normal distribution used for confidence
+    /// intervals, 1.96 for 95%.
+    #[serde(default = "default_z")]
+    pub z: f64,
+}
+
+fn default_z() -> f64 {
+    1.96
+}
+
+impl Default for NoiseConfig {
+    fn default() -> Self {
+        NoiseConfig {
+            samples: 1,
+            reevaluate_elites: false,
+            z: default_z(),
+        }
+    }
+}
+
+/// Running mean and variance of one member's fitness samples, using Welford's
+/// algorithm.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RunningStats {
+    count: u64,
+    mean: f64,
+    /// Sum of squared differences from the mean.
+    m2: f64,
+}
+
+impl RunningStats {
+    pub fn push(&mut self, value: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    /// Combines the samples of `other` into `self`.
+    pub fn merge(&mut self, other: &RunningStats) {
+        if other.count == 0 {
+            return;
+        }
+        let count = self.count + other.count;
+        let delta = other.mean - self.mean;
+        self.mean += delta * other.count as f64 / count as f64;
+        self.m2 += other.m2 + delta * delta * (self.count * other.count) as f64 / count as f64;
+        self.count = count;
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.mean)
+    }
+
+    /// Sample variance, `None` with fewer than two samples.
+    pub fn variance(&self) -> Option<f64> {
+        (self.count > 1).then(|| self.m2 / (self.count - 1) as f64)
+    }
+
+    /// Standard error of the mean.
+    pub fn std_error(&self) -> Option<f64> {
+        self.variance().map(|v| (v / self.count as f64).sqrt())
+    }
+
+    /// `(lower, upper)` bounds of the mean, `z` standard errors either side.
+    pub fn confidence_interval(&self, z: f64) -> Option<(f64, f64)> {
+        self.std_error()
+            .map(|e| (self.mean - z * e, self.mean + z * e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RunningStats;
+
+    #[test]
+    fn test_running_stats() {
+        let samples = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        let mut all = RunningStats::default();
+        samples.iter().for_each(|s| all.push(*s));
+        assert_eq!(Some(5.0), all.mean());
+        assert!((all.variance().unwrap() - 32.0 / 7.0).abs() < 1e-12);
+
+        let (mut first, mut second) = (RunningStats::default(), RunningStats::default());
+        samples[..3].iter().for_each(|s| first.push(*s));
+        samples[3..].iter().for_each(|s| second.push(*s));
+        first.merge(&second);
+        assert_eq!(8, first.count());
+        assert!((first.mean().unwrap() - 5.0).abs() < 1e-12);
+        assert!((first.variance().unwrap() - 32.0 / 7.0).abs() < 1e-12);
+
+        let (lower, upper) = all.confidence_interval(1.96).unwrap();
+        assert!(lower < 5.0 && 5.0 < upper);
+        assert!((upper - lower - 2.0 * 1.96 * (32.0 / 7.0 / 8.0f64).sqrt()).abs() < 1e-12);
+
+        let mut single = RunningStats::default();
+        single.push(3.0);
+        assert_eq!(None, single.confidence_interval(1.96));
+        assert_eq!(None, RunningStats::default().mean());
+    }
+}
@@ -0,0 +1,403 @@
+use std::cmp::Ordering;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "parallel")]
+use crate::parallel::par_map_seeded;
+use crate::{
+    parallel::map_seeded,
+    population::PopulationConfig,
+    traits::{Crossover, Generate, MultiObjectiveFitness, Mutate},
+};
+
+/// NSGA-II driver for multi-objective problems.
+///
+/// Each generation creates `crossover_count + mutate_count` offspring using
+/// binary tournaments on (front, crowding distance), then keeps the best
+/// `pop_size` of parents and offspring by non-dominated sorting.
+/// `elitism_count` and `selection` are not used, as NSGA-II is elitist by construction.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Nsga2<T: Generate + Crossover + Mutate + MultiObjectiveFitness> {
+    pub members: Vec<T>,
+    /// Objectives of each member, in the same order as `members`.
+    pub objectives: Vec<Option<Vec<f64>>>,
+    pub config: PopulationConfig,
+    generation: i64,
+    seed: [u8; 32],
+    /// Evaluates batches of members; `None` evaluates them one after another.
+    #[serde(skip)]
+    evaluator: Option<Evaluator<T>>,
+}
+
+/// Calculates the objectives of each member with the seed at its position.
+type Evaluator<T> = fn(&mut [T], &[[u8; 32]]) -> Vec<Option<Vec<f64>>>;
+
+fn objectives_of<T: MultiObjectiveFitness>(
+    members: &mut [T],
+    seeds: &[[u8; 32]],
+) -> Vec<Option<Vec<f64>>> {
+    map_seeded(members, seeds, |m, seed| m.calculate_objectives(seed))
+}
+
+#[cfg(feature = "parallel")]
+fn par_objectives_of<T: MultiObjectiveFitness + Send>(
+    members: &mut [T],
+    seeds: &[[u8; 32]],
+) -> Vec<Option<Vec<f64>>> {
+    par_map_seeded(members, seeds, |m, seed| m.calculate_objectives(seed))
+}
+
+impl<T: Generate + Crossover + Mutate + MultiObjectiveFitness + Clone> Nsga2<T> {
+    pub fn new(config: PopulationConfig) -> Nsga2<T> {
+        let mut rng: StdRng = SeedableRng::from_seed(config.seed);
+        let mut members: Vec<T> = Vec::new();
+        for _ in 1..=config.pop_size {
+            members.push(T::generate(rng.gen()));
+        }
+        Nsga2 {
+            seed: rng.gen(),
+            members,
+            objectives: Vec::new(),
+            config,
+            generation: 1,
+            evaluator: None,
+        }
+    }
+
+    pub fn get_generation(&self) -> i64 {
+        self.generation
+    }
+
+    /// Members of the first non-dominated front, with their objectives.
+    pub fn pareto_front(&self) -> Vec<(&T, &Vec<f64>)> {
+        match non_dominated_sort(&self.objectives).first() {
+            Some(front) => front
+                .iter()
+                .filter_map(|i| self.objectives[*i].as_ref().map(|o| (&self.members[*i], o)))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Breeds and selects the next generation. Does nothing without members
+    /// to breed from.
+    pub fn tick(&mut self) {
+        if self.members.is_empty() {
+            return;
+        }
+        let mut rng: StdRng = SeedableRng::from_seed(self.seed);
+
+        if self.objectives.len() != self.members.len() {
+            let seeds: Vec<[u8; 32]> = self.members.iter().map(|_| rng.gen()).collect();
+            let evaluate = self.evaluator.unwrap_or(objectives_of::<T>);
+            self.objectives = evaluate(&mut self.members, &seeds);
+        }
+
+        let (front_of, crowding) = rank(&self.objectives);
+        let tournament = |rng: &mut StdRng| {
+            let a = rng.gen_range(0..self.members.len());
+            let b = rng.gen_range(0..self.members.len());
+            match front_of[a].cmp(&front_of[b]) {
+                Ordering::Less => a,
+                Ordering::Greater => b,
+                Ordering::Equal if crowding[b] > crowding[a] => b,
+                Ordering::Equal => a,
+            }
+        };
+
+        let mut offspring: Vec<T> = Vec::new();
+        (0..self.config.mutate_count).for_each(|_| {
+            let parent = tournament(&mut rng);
+            offspring.push(self.members[parent].mutate(&self.config.mutation_config, rng.gen()));
+        });
+        (0..self.config.crossover_count).for_each(|_| {
+            let first = tournament(&mut rng);
+            let second = tournament(&mut rng);
+            offspring.push(self.members[first].crossover(
+                &self.members[second],
+                &self.config.crossover_config,
+                rng.gen(),
+            ));
+        });
+
+        let seeds: Vec<[u8; 32]> = offspring.iter().map(|_| rng.gen()).collect();
+        let mut objectives = std::mem::take(&mut self.objectives);
+        let evaluate = self.evaluator.unwrap_or(objectives_of::<T>);
+        objectives.extend(evaluate(&mut offspring, &seeds));
+        let mut members = std::mem::take(&mut self.members);
+        members.extend(offspring);
+
+        // Fill the next generation front by front, breaking the last one by crowding.
+        let mut survivors: Vec<usize> = Vec::with_capacity(self.config.pop_size);
+        for front in non_dominated_sort(&objectives) {
+            if survivors.len() + front.len() <= self.config.pop_size {
+                survivors.extend(front);
+            } else {
+                let distances = crowding_distance(&objectives, &front);
+                let mut order: Vec<usize> = (0..front.len()).collect();
+                order.sort_by(|a, b| distances[*b].total_cmp(&distances[*a]));
+                let remaining = self.config.pop_size - survivors.len();
+                survivors.extend(order.into_iter().take(remaining).map(|i| front[i]));
+            }
+            if survivors.len() >= self.config.pop_size {
+                break;
+            }
+        }
+
+        let mut keep = vec![false; members.len()];
+        survivors.iter().for_each(|i| keep[*i] = true);
+        let mut keep_iter = keep.iter();
+        members.retain(|_| *keep_iter.next().unwrap());
+        let mut keep_iter = keep.iter();
+        objectives.retain(|_| *keep_iter.next().unwrap());
+
+        self.members = members;
+        self.objectives = objectives;
+        self.generation += 1;
+        self.seed = rng.gen();
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<T: Generate + Crossover + Mutate + MultiObjectiveFitness + Clone + Send> Nsga2<T> {
+    /// Evaluates members across threads. Each member keeps its own seed, so
+    /// runs are the same as serial ones.
+    pub fn use_parallel_evaluation(&mut self) {
+        self.evaluator = Some(par_objectives_of::<T>);
+    }
+}
+
+/// True if `a` is at least as good as `b` in every objective and better in one.
+pub fn dominates(a: &[f64], b: &[f64]) -> bool {
+    let mut better = false;
+    for (x, y) in a.iter().zip(b.iter()) {
+        if x < y {
+            return false;
+        }
+        if x > y {
+            better = true;
+        }
+    }
+    better
+}
+
+/// Fast non-dominated sort. Returns fronts of indices, best first. Members
+/// without objectives, or with NaN objectives, form the final front.
+pub fn non_dominated_sort(objectives: &[Option<Vec<f64>>]) -> Vec<Vec<usize>> {
+    let valid: Vec<usize> = (0..objectives.len())
+        .filter(|i| {
+            objectives[*i]
+                .as_ref()
+                .is_some_and(|o| o.iter().all(|v| !v.is_nan()))
+        })
+        .collect();
+    let invalid: Vec<usize> = (0..objectives.len())
+        .filter(|i| !valid.contains(i))
+        .collect();
+
+    let mut dominated_by: Vec<usize> = vec![0; objectives.len()];
+    let mut dominating: Vec<Vec<usize>> = vec![Vec::new(); objectives.len()];
+    for (n, &p) in valid.iter().enumerate() {
+        for &q in valid.iter().skip(n + 1) {
+            let (a, b) = (
+                objectives[p].as_ref().unwrap(),
+                objectives[q].as_ref().unwrap(),
+            );
+            if dominates(a, b) {
+                dominating[p].push(q);
+                dominated_by[q] += 1;
+            } else if dominates(b, a) {
+                dominating[q].push(p);
+                dominated_by[p] += 1;
+            }
+        }
+    }
+
+    let mut fronts = Vec::new();
+    let mut current: Vec<usize> = valid
+        .iter()
+        .copied()
+        .filter(|i| dominated_by[*i] == 0)
+        .collect();
+    while !current.is_empty() {
+        let mut next = Vec::new();
+        for &p in &current {
+            for &q in &dominating[p] {
+                dominated_by[q] -= 1;
+                if dominated_by[q] == 0 {
+                    next.push(q);
+                }
+            }
+        }
+        next.sort_unstable();
+        fronts.push(current);
+        current = next;
+    }
+    if !invalid.is_empty() {
+        fronts.push(invalid);
+    }
+    fronts
+}
+
+/// Crowding distance of each member of `front`, in the same order. Boundary
+/// members of every objective get `f64::INFINITY`.
+pub fn crowding_distance(objectives: &[Option<Vec<f64>>], front: &[usize]) -> Vec<f64> {
+    let mut distances = vec![0.0; front.len()];
+    let values: Vec<&Vec<f64>> = match front
+        .iter()
+        .map(|i| objectives[*i].as_ref())
+        .collect::<Option<Vec<_>>>()
+    {
+        Some(values) => values,
+        None => return distances,
+    };
+    let count = values.iter().map(|v| v.len()).min().unwrap_or(0);
+
+    (0..count).for_each(|m| {
+        let mut order: Vec<usize> = (0..front.len()).collect();
+        order.sort_by(|a, b| values[*a][m].total_cmp(&values[*b][m]));
+        let (first, last) = (order[0], order[order.len() - 1]);
+        distances[first] = f64::INFINITY;
+        distances[last] = f64::INFINITY;
+        let range = values[last][m] - values[first][m];
+        if range <= 0.0 {
+            return;
+        }
+        for w in order.windows(3) {
+            distances[w[1]] += (values[w[2]][m] - values[w[0]][m]) / range;
+        }
+    });
+    distances
+}
+
+/// Front number and crowding distance of every member.
+fn rank(objectives: &[Option<Vec<f64>>]) -> (Vec<usize>, Vec<f64>) {
+    let mut front_of = vec![0; objectives.len()];
+    let mut crowding = vec![0.0; objectives.len()];
+    for (f, front) in non_dominated_sort(objectives).into_iter().enumerate() {
+        let distances = crowding_distance(objectives, &front);
+        front.iter().zip(distances).for_each(|(i, d)| {
+            front_of[*i] = f;
+            crowding[*i] = d;
+        });
+    }
+    (front_of, crowding)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crowding_distance, non_dominated_sort, Nsga2};
+    use crate::{
+        population::{CrossoverConfig, MutationConfig, PopulationConfig},
+        real_vector::{RealVector, RepairPolicy},
+        traits::{Crossover, Generate, MultiObjectiveFitness, Mutate},
+    };
+
+    #[test]
+    fn test_non_dominated_sort() {
+        let objectives = vec![
+            Some(vec![1.0, 5.0]),
+            Some(vec![2.0, 2.0]),
+            None,
+            Some(vec![5.0, 1.0]),
+            Some(vec![1.0, 1.0]),
+            Some(vec![0.5, 4.0]),
+            Some(vec![f64::NAN, 9.0]),
+        ];
+        assert_eq!(
+            vec![vec![0, 1, 3], vec![4, 5], vec![2, 6]],
+            non_dominated_sort(&objectives)
+        );
+
+        let distances = crowding_distance(&objectives, &[0, 1, 3]);
+        assert_eq!(f64::INFINITY, distances[0]);
+        assert_eq!(f64::INFINITY, distances[2]);
+        assert_eq!(2.0, distances[1]);
+    }
+
+    #[derive(Clone, Debug, Default)]
+    struct Schaffer(RealVector);
+
+    impl Generate for Schaffer {
+        fn generate(seed: [u8; 32]) -> Self {
+            Schaffer(RealVector::generate_bounded(
+                vec![(-10.0, 10.0)],
+                RepairPolicy::Clamp,
+                seed,
+            ))
+        }
+    }
+
+    impl Mutate for Schaffer {
+        fn mutate(&self, config: &MutationConfig, seed: [u8; 32]) -> Self {
+            Schaffer(self.0.mutate(config, seed))
+        }
+    }
+
+    impl Crossover for Schaffer {
+        fn crossover(&self, other: &Self, config: &CrossoverConfig, seed: [u8; 32]) -> Self {
+            Schaffer(self.0.crossover(&other.0, config, seed))
+        }
+    }
+
+    impl MultiObjectiveFitness for Schaffer {
+        fn calculate_objectives(&mut self, _seed: [u8; 32]) -> Option<Vec<f64>> {
+            let x = self.0.get_data()[0];
+            Some(vec![-(x * x), -((x - 2.0) * (x - 2.0))])
+        }
+    }
+
+    #[test]
+    fn test_converges_to_pareto_set() {
+        let config = PopulationConfig {
+            pop_size: 20,
+            crossover_count: 10,
+            mutate_count: 10,
+            mutation_config: MutationConfig {
+                gene_mutation_chance: 0.5,
+                ..Default::default()
+            },
+            seed: [3; 32],
+            ..Default::default()
+        };
+        let mut nsga: Nsga2<Schaffer> = Nsga2::new(config.clone());
+        (0..30).for_each(|_| nsga.tick());
+
+        assert_eq!(20, nsga.members.len());
+        assert_eq!(20, nsga.objectives.len());
+        let front = nsga.pareto_front();
+        assert!(front.len() > 10);
+        front
+            .iter()
+            .for_each(|(m, _)| assert!((-0.01..=2.01).contains(&m.0.get_data()[0])));
+
+        let mut empty: Nsga2<Schaffer> = Nsga2::new(PopulationConfig {
+            pop_size: 0,
+            ..config
+        });
+        empty.tick();
+        assert!(empty.members.is_empty());
+        assert_eq!(1, empty.get_generation());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_matches_serial() {
+        let config = PopulationConfig {
+            pop_size: 20,
+            crossover_count: 10,
+            mutate_count: 10,
+            seed: [5; 32],
+            ..Default::default()
+        };
+        let mut serial: Nsga2<Schaffer> = Nsga2::new(config.clone());
+        let mut parallel: Nsga2<Schaffer> = Nsga2::new(config);
+        parallel.use_parallel_evaluation();
+        (0..5).for_each(|_| {
+            serial.tick();
+            parallel.tick();
+        });
+        assert_eq!(serial.objectives, parallel.objectives);
+    }
+}
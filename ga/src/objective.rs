@@ -0,0 +1,89 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+use crate::traits::FitnessValue;
+
+/// Whether larger or smaller fitness values are better.
+///
+/// Members without a fitness, or with a NaN fitness, are worse than every
+/// member with a number, and equal to each other.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Objective {
+    #[default]
+    Maximize,
+    Minimize,
+}
+
+impl Objective {
+    /// Total ordering with the better fitness first, for sorting best to worst.
+    pub fn compare<F: FitnessValue>(&self, a: &Option<F>, b: &Option<F>) -> Ordering {
+        match (usable(a), usable(b)) {
+            (Some(a), Some(b)) => match self {
+                Objective::Maximize => b.compare(a),
+                Objective::Minimize => a.compare(b),
+            },
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+
+    pub fn is_better<F: FitnessValue>(&self, a: &Option<F>, b: &Option<F>) -> bool {
+        self.compare(a, b) == Ordering::Less
+    }
+
+    /// The better of two fitnesses, preferring `a` on ties.
+    pub fn best<F: FitnessValue>(&self, a: Option<F>, b: Option<F>) -> Option<F> {
+        if self.is_better(&b, &a) {
+            b
+        } else {
+            a
+        }
+    }
+
+    /// Maps a fitness onto an `f64` scale where larger is better, with invalid
+    /// values as `None`. Fitness-proportional selection works on these values.
+    pub fn oriented<F: FitnessValue>(&self, fitness: &Option<F>) -> Option<f64> {
+        usable(fitness).map(|f| match self {
+            Objective::Maximize => f.to_f64(),
+            Objective::Minimize => -f.to_f64(),
+        })
+    }
+}
+
+fn usable<F: FitnessValue>(fitness: &Option<F>) -> Option<&F> {
+    fitness.as_ref().filter(|f| f.is_valid())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Objective;
+
+    #[test]
+    fn test_ordering() {
+        let mut fitnesses = [Some(2.0), None, Some(f64::NAN), Some(-1.0), Some(5.0)];
+        fitnesses.sort_by(|a, b| Objective::Minimize.compare(a, b));
+        assert_eq!(Some(-1.0), fitnesses[0]);
+        assert_eq!(Some(5.0), fitnesses[2]);
+        assert!(fitnesses[3..].iter().all(|f| f.is_none_or(|f| f.is_nan())));
+
+        fitnesses.sort_by(|a, b| Objective::Maximize.compare(a, b));
+        assert_eq!(Some(5.0), fitnesses[0]);
+        assert_eq!(Some(-1.0), fitnesses[2]);
+
+        assert!(Objective::Maximize.is_better(&Some(f64::NEG_INFINITY), &Some(f64::NAN)));
+        assert_eq!(Some(1.0), Objective::Minimize.best(None, Some(1.0)));
+        assert_eq!(Some(-3.0), Objective::Minimize.oriented(&Some(3.0)));
+        assert_eq!(None, Objective::Maximize.oriented(&Some(f64::NAN)));
+
+        // Feasible first, then cheapest.
+        let mut lexicographic = [Some((1, 2.0)), Some((0, 9.0)), None, Some((0, 3.0))];
+        lexicographic.sort_by(|a, b| Objective::Minimize.compare(a, b));
+        assert_eq!(
+            [Some((0, 3.0)), Some((0, 9.0)), Some((1, 2.0)), None],
+            lexicographic
+        );
+        assert_eq!(Some(-1.0), Objective::Minimize.oriented(&Some((1, 2.0))));
+    }
+}
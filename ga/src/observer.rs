@@ -0,0 +1,158 @@
+use std::fmt;
+
+use crate::stats::GenerationStats;
+
+/// The operator that produced a member of the next generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Elitism,
+    Mutation,
+    Crossover,
+    Generated,
+}
+
+/// Returned from `Observer::on_generation_end` to continue or stop the run.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    #[default]
+    Continue,
+    Stop,
+}
+
+/// Hooks called by `Population::tick`. Every method does nothing by default.
+///
+/// ```
+/// use ga::{observer::{Control, Observer}, stats::GenerationStats};
+///
+/// struct StopAt(f64);
+///
+/// impl<T> Observer<T> for StopAt {
+///     fn on_generation_end(&mut self, stats: &GenerationStats, _members: &[T]) -> Control {
+///         println!("{}: {:?}", stats.generation, stats.best);
+///         match stats.best {
+///             Some(best) if best >= self.0 => Control::Stop,
+///             _ => Control::Continue,
+///         }
+///     }
+/// }
+/// ```
+pub trait Observer<T> {
+    /// Before the members of `generation` are evaluated.
+    fn on_generation_start(&mut self, _generation: i64, _members: &[T]) {}
+
+    /// After the members have been evaluated and sorted, best first.
+    fn on_evaluated(&mut self, _generation: i64, _members: &[T]) {}
+
+    /// After a member of the next generation is created, before it is evaluated.
+    /// `parents` are indices into the sorted members.
+    fn on_offspring(&mut self, _offspring: &T, _operator: Operator, _parents: &[usize]) {}
+
+    /// Before crossing parents that `Crossover::can_cross` says can't be
+    /// combined. The offspring is whatever `crossover` makes of them, usually a
+    /// copy of the first parent.
+    fn on_incompatible_crossover(&mut self, _parents: &[usize]) {}
+
+    /// After the next generation has been evaluated.
+    fn on_generation_end(&mut self, _stats: &GenerationStats, _members: &[T]) -> Control {
+        Control::Continue
+    }
+}
+
+/// Prints a progress line to stderr every `every` generations.
+#[derive(Debug, Clone)]
+pub struct ProgressLogger {
+    every: i64,
+}
+
+impl ProgressLogger {
+    pub fn new(every: usize) -> Self {
+        ProgressLogger {
+            every: every.max(1) as i64,
+        }
+    }
+}
+
+impl<T> Observer<T> for ProgressLogger {
+    fn on_generation_end(&mut self, stats: &GenerationStats, _members: &[T]) -> Control {
+        if stats.generation % self.every == 0 {
+            eprintln!(
+                "generation {}: best {:?}, mean {:?}, {} evaluations{}",
+                stats.generation,
+                stats.best,
+                stats.mean,
+                stats.total_evaluations,
+                stats
+                    .diversity
+                    .as_ref()
+                    .and_then(|d| d.mean_distance)
+                    .map(|d| format!(", mean distance {:.3}", d))
+                    .unwrap_or_default()
+            );
+        }
+        Control::Continue
+    }
+}
+
+/// Observers registered on a population. They are not serialized, and are
+/// `Send` so that populations can be moved between threads.
+pub struct Observers<T>(Vec<Box<dyn Observer<T> + Send>>);
+
+impl<T> Observers<T> {
+    pub fn push(&mut self, observer: Box<dyn Observer<T> + Send>) {
+        self.0.push(observer);
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(crate) fn generation_start(&mut self, generation: i64, members: &[T]) {
+        self.0
+            .iter_mut()
+            .for_each(|o| o.on_generation_start(generation, members));
+    }
+
+    pub(crate) fn evaluated(&mut self, generation: i64, members: &[T]) {
+        self.0
+            .iter_mut()
+            .for_each(|o| o.on_evaluated(generation, members));
+    }
+
+    pub(crate) fn offspring(&mut self, offspring: &T, operator: Operator, parents: &[usize]) {
+        self.0
+            .iter_mut()
+            .for_each(|o| o.on_offspring(offspring, operator, parents));
+    }
+
+    pub(crate) fn incompatible_crossover(&mut self, parents: &[usize]) {
+        self.0
+            .iter_mut()
+            .for_each(|o| o.on_incompatible_crossover(parents));
+    }
+
+    /// Every observer is called, and the run stops if any of them asks to.
+    pub(crate) fn generation_end(&mut self, stats: &GenerationStats, members: &[T]) -> Control {
+        self.0.iter_mut().fold(Control::Continue, |control, o| {
+            match o.on_generation_end(stats, members) {
+                Control::Stop => Control::Stop,
+                Control::Continue => control,
+            }
+        })
+    }
+}
+
+impl<T> Default for Observers<T> {
+    fn default() -> Self {
+        Observers(Vec::new())
+    }
+}
+
+impl<T> fmt::Debug for Observers<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Observers({})", self.0.len())
+    }
+}
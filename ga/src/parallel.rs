@@ -0,0 +1,33 @@
+//! Fitness evaluation helpers. With the `parallel` feature, populations of
+//! `Send` genomes can opt in to evaluating across threads. Every item gets its
+//! own pre-drawn seed, so the results are identical to the serial path.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Calls `f` on every item with the seed at the same position, returning the results in order.
+pub fn map_seeded<T, R, F>(items: &mut [T], seeds: &[[u8; 32]], f: F) -> Vec<R>
+where
+    F: Fn(&mut T, [u8; 32]) -> R,
+{
+    items
+        .iter_mut()
+        .zip(seeds.iter())
+        .map(|(item, seed)| f(item, *seed))
+        .collect()
+}
+
+/// Like `map_seeded`, but across threads.
+#[cfg(feature = "parallel")]
+pub fn par_map_seeded<T, R, F>(items: &mut [T], seeds: &[[u8; 32]], f: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(&mut T, [u8; 32]) -> R + Sync,
+{
+    items
+        .par_iter_mut()
+        .zip(seeds.par_iter())
+        .map(|(item, seed)| f(item, *seed))
+        .collect()
+}
@@ -0,0 +1,395 @@
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    population::{probability, CrossoverConfig, Genome, MutationConfig},
+    traits::{Crossover, Distance, FitnessRetrieve, FitnessValue, Generate, Genes, Mutate},
+};
+
+pub const DEFAULT_LEN: usize = 20;
+
+/// Crossover operators that always produce a valid permutation.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PermutationCrossover {
+    /// Partially mapped crossover.
+    #[default]
+    Pmx,
+    /// Order crossover (OX1).
+    Order,
+    Cycle,
+    EdgeRecombination,
+}
+
+/// Mutation operators that always produce a valid permutation.
+///
+/// `Swap` is applied per position with `gene_mutation_chance`; the others are
+/// applied once per mutation.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PermutationMutation {
+    #[default]
+    Swap,
+    Insertion,
+    Inversion,
+    Scramble,
+}
+
+/// An ordering of the indices `0..len`, for scheduling and routing problems.
+/// Deserializing data that isn't such an ordering fails.
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "Unchecked<F>", bound(deserialize = "F: FitnessValue"))]
+pub struct Permutation<F = f64> {
+    inner: Genome<Vec<usize>, F>,
+}
+
+/// A deserialized `Permutation` whose data hasn't been checked yet.
+#[derive(Deserialize)]
+#[serde(bound = "F: FitnessValue")]
+struct Unchecked<F> {
+    inner: Genome<Vec<usize>, F>,
+}
+
+impl<F> TryFrom<Unchecked<F>> for Permutation<F> {
+    type Error = String;
+
+    fn try_from(unchecked: Unchecked<F>) -> Result<Self, String> {
+        let data = &unchecked.inner.data;
+        if !is_permutation(data) {
+            return Err(format!(
+                "{:?} is not an ordering of 0..{}",
+                data,
+                data.len()
+            ));
+        }
+        Ok(Permutation {
+            inner: unchecked.inner,
+        })
+    }
+}
+
+impl<F: FitnessValue> Permutation<F> {
+    pub fn get_data(&self) -> &Vec<usize> {
+        &self.inner.data
+    }
+    /// Number of times a fitness has been set on this genome.
+    pub fn get_evaluations(&self) -> u64 {
+        self.inner.evaluations
+    }
+    pub fn generate_length(length: usize, seed: [u8; 32]) -> Self {
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let mut data: Vec<usize> = (0..length).collect();
+        data.shuffle(&mut rng);
+        Permutation::from_data(data)
+    }
+    fn from_data(data: Vec<usize>) -> Self {
+        Permutation {
+            inner: Genome {
+                data,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl<F: FitnessValue> Generate for Permutation<F> {
+    fn generate(seed: [u8; 32]) -> Self {
+        Permutation::generate_length(DEFAULT_LEN, seed)
+    }
+}
+
+impl<F: FitnessValue> Mutate for Permutation<F> {
+    fn mutate(&self, config: &MutationConfig, seed: [u8; 32]) -> Self {
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let mut data = self.inner.data.clone();
+        let len = data.len();
+        if len < 2 {
+            return Permutation::from_data(data);
+        }
+
+        match config.permutation {
+            PermutationMutation::Swap => (0..len).for_each(|i| {
+                if rng.gen_bool(probability(config.gene_mutation_chance)) {
+                    data.swap(i, rng.gen_range(0..len));
+                }
+            }),
+            PermutationMutation::Insertion => {
+                let item = data.remove(rng.gen_range(0..len));
+                data.insert(rng.gen_range(0..len), item);
+            }
+            PermutationMutation::Inversion => {
+                let (start, end) = segment(len, &mut rng);
+                data[start..end].reverse();
+            }
+            PermutationMutation::Scramble => {
+                let (start, end) = segment(len, &mut rng);
+                data[start..end].shuffle(&mut rng);
+            }
+        }
+        Permutation::from_data(data)
+    }
+}
+
+impl<F: FitnessValue> Crossover for Permutation<F> {
+    /// Permutations of different lengths can't be crossed, and the offspring
+    /// is a copy of `self`; see `can_cross`.
+    fn crossover(&self, other: &Self, config: &CrossoverConfig, seed: [u8; 32]) -> Self {
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let first = &self.inner.data;
+        let second = &other.inner.data;
+        if first.len() != second.len() || first.len() < 2 {
+            return Permutation::from_data(first.clone());
+        }
+
+        let data = match config.permutation {
+            PermutationCrossover::Pmx => pmx(first, second, segment(first.len(), &mut rng)),
+            PermutationCrossover::Order => order(first, second, segment(first.len(), &mut rng)),
+            PermutationCrossover::Cycle => cycle(first, second),
+            PermutationCrossover::EdgeRecombination => edge_recombination(first, second, &mut rng),
+        };
+        Permutation::from_data(data)
+    }
+    fn can_cross(&self, other: &Self) -> bool {
+        self.inner.data.len() == other.inner.data.len()
+    }
+}
+
+impl<F: FitnessValue> FitnessRetrieve for Permutation<F> {
+    type Value = F;
+    fn get_fitness(&self) -> Option<F> {
+        self.inner.fitness.clone()
+    }
+    fn set_fitness(&mut self, fitness: Option<F>) {
+        self.inner.set_fitness(fitness);
+    }
+}
+
+/// The positions holding different items, plus the difference in length.
+impl<F> Distance for Permutation<F> {
+    fn distance(&self, other: &Self) -> f64 {
+        let (a, b) = (&self.inner.data, &other.inner.data);
+        let differing = a.iter().zip(b).filter(|(x, y)| x != y).count();
+        (differing + a.len().abs_diff(b.len())) as f64
+    }
+}
+
+impl<F> Genes for Permutation<F> {
+    type Gene = usize;
+    fn genes(&self) -> &[usize] {
+        &self.inner.data
+    }
+}
+
+/// A random non-empty range `start..end` within `0..len`.
+fn segment(len: usize, rng: &mut StdRng) -> (usize, usize) {
+    let a = rng.gen_range(0..len);
+    let b = rng.gen_range(0..len);
+    (a.min(b), a.max(b) + 1)
+}
+
+/// True if `data` holds each of `0..data.len()` exactly once.
+fn is_permutation(data: &[usize]) -> bool {
+    let mut seen = vec![false; data.len()];
+    data.iter()
+        .all(|&v| v < seen.len() && !std::mem::replace(&mut seen[v], true))
+}
+
+fn positions(data: &[usize]) -> Vec<usize> {
+    let mut positions = vec![0; data.len()];
+    data.iter().enumerate().for_each(|(i, v)| positions[*v] = i);
+    positions
+}
+
+fn pmx(first: &[usize], second: &[usize], (start, end): (usize, usize)) -> Vec<usize> {
+    let mut offspring: Vec<Option<usize>> = vec![None; first.len()];
+    let second_positions = positions(second);
+    (start..end).for_each(|i| offspring[i] = Some(first[i]));
+
+    for (i, &value) in second.iter().enumerate().take(end).skip(start) {
+        if first[start..end].contains(&value) {
+            continue;
+        }
+        // Follow the mapping until we land outside the copied segment.
+        let mut j = i;
+        while (start..end).contains(&j) {
+            j = second_positions[first[j]];
+        }
+        offspring[j] = Some(value);
+    }
+
+    offspring
+        .into_iter()
+        .zip(second.iter())
+        .map(|(o, s)| o.unwrap_or(*s))
+        .collect()
+}
+
+fn order(first: &[usize], second: &[usize], (start, end): (usize, usize)) -> Vec<usize> {
+    let len = first.len();
+    let mut offspring: Vec<Option<usize>> = vec![None; len];
+    let mut used = vec![false; len];
+    (start..end).for_each(|i| {
+        offspring[i] = Some(first[i]);
+        used[first[i]] = true;
+    });
+
+    let mut fill = (end..len).chain(0..start);
+    (0..len)
+        .map(|i| second[(end + i) % len])
+        .filter(|v| !used[*v])
+        .for_each(|v| offspring[fill.next().unwrap()] = Some(v));
+
+    offspring.into_iter().map(|o| o.unwrap()).collect()
+}
+
+fn cycle(first: &[usize], second: &[usize]) -> Vec<usize> {
+    let first_positions = positions(first);
+    let mut offspring: Vec<Option<usize>> = vec![None; first.len()];
+    let mut from_first = true;
+
+    for start in 0..first.len() {
+        if offspring[start].is_some() {
+            continue;
+        }
+        let source = if from_first { first } else { second };
+        let mut i = start;
+        while offspring[i].is_none() {
+            offspring[i] = Some(source[i]);
+            i = first_positions[second[i]];
+        }
+        from_first = !from_first;
+    }
+
+    offspring.into_iter().map(|o| o.unwrap()).collect()
+}
+
+fn edge_recombination(first: &[usize], second: &[usize], rng: &mut StdRng) -> Vec<usize> {
+    let len = first.len();
+    let mut neighbours: Vec<Vec<usize>> = vec![Vec::with_capacity(4); len];
+    for parent in [first, second] {
+        for i in 0..len {
+            let (prev, next) = (parent[(i + len - 1) % len], parent[(i + 1) % len]);
+            for n in [prev, next] {
+                if !neighbours[parent[i]].contains(&n) {
+                    neighbours[parent[i]].push(n);
+                }
+            }
+        }
+    }
+
+    let mut offspring = Vec::with_capacity(len);
+    let mut visited = vec![false; len];
+    let mut current = first[0];
+    loop {
+        offspring.push(current);
+        visited[current] = true;
+        if offspring.len() == len {
+            break;
+        }
+        neighbours
+            .iter_mut()
+            .for_each(|n| n.retain(|v| *v != current));
+
+        let candidates = &neighbours[current];
+        current = match candidates.iter().map(|c| neighbours[*c].len()).min() {
+            Some(fewest) => {
+                let best: Vec<usize> = candidates
+                    .iter()
+                    .copied()
+                    .filter(|c| neighbours[*c].len() == fewest)
+                    .collect();
+                *best.choose(rng).unwrap()
+            }
+            None => {
+                let unvisited: Vec<usize> = (0..len).filter(|v| !visited[*v]).collect();
+                *unvisited.choose(rng).unwrap()
+            }
+        };
+    }
+    offspring
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cycle, order, pmx, Permutation, PermutationCrossover, PermutationMutation};
+    use crate::{
+        population::{CrossoverConfig, MutationConfig},
+        traits::{Crossover, Generate, Mutate},
+    };
+
+    fn is_valid(data: &[usize]) -> bool {
+        let mut sorted = data.to_vec();
+        sorted.sort_unstable();
+        sorted.into_iter().eq(0..data.len())
+    }
+
+    #[test]
+    fn test_known_offspring() {
+        let first = [0, 1, 2, 3, 4, 5, 6, 7, 8];
+        let second = [8, 2, 6, 7, 1, 5, 4, 0, 3];
+        assert_eq!(
+            vec![8, 2, 6, 3, 4, 5, 1, 0, 7],
+            pmx(&first, &second, (3, 6))
+        );
+        assert_eq!(
+            vec![6, 7, 1, 3, 4, 5, 0, 8, 2],
+            order(&first, &second, (3, 6))
+        );
+        assert_eq!(vec![0, 2, 6, 3, 1, 5, 4, 7, 8], cycle(&first, &second));
+    }
+
+    #[test]
+    fn test_operators_keep_permutation_valid() {
+        let crossovers = [
+            PermutationCrossover::Pmx,
+            PermutationCrossover::Order,
+            PermutationCrossover::Cycle,
+            PermutationCrossover::EdgeRecombination,
+        ];
+        let mutations = [
+            PermutationMutation::Swap,
+            PermutationMutation::Insertion,
+            PermutationMutation::Inversion,
+            PermutationMutation::Scramble,
+        ];
+        for seed in 0..20u8 {
+            let first: Permutation = Permutation::generate([seed; 32]);
+            let second = Permutation::generate([seed + 100; 32]);
+            for permutation in crossovers.iter().cloned() {
+                let config = CrossoverConfig {
+                    permutation,
+                    ..Default::default()
+                };
+                let offspring = first.crossover(&second, &config, [seed; 32]);
+                assert!(is_valid(offspring.get_data()), "{config:?}");
+            }
+            for permutation in mutations.iter().cloned() {
+                let config = MutationConfig {
+                    gene_mutation_chance: 0.3,
+                    permutation,
+                    ..Default::default()
+                };
+                let mutated = first.mutate(&config, [seed; 32]);
+                assert!(is_valid(mutated.get_data()), "{config:?}");
+            }
+        }
+
+        let first: Permutation = Permutation::generate([1; 32]);
+        let shorter = Permutation::generate_length(5, [2; 32]);
+        assert!(!first.can_cross(&shorter));
+        let offspring = first.crossover(&shorter, &CrossoverConfig::default(), [3; 32]);
+        assert_eq!(first.get_data(), offspring.get_data());
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let permutation: Permutation = Permutation::generate_length(4, [1; 32]);
+        let json = serde_json::to_string(&permutation).unwrap();
+        let restored: Permutation = serde_json::from_str(&json).unwrap();
+        assert_eq!(permutation.get_data(), restored.get_data());
+
+        for data in ["[0,1,1]", "[0,1,3]"] {
+            let json = format!("{{\"inner\":{{\"data\":{},\"fitness\":null}}}}", data);
+            let error = serde_json::from_str::<Permutation>(&json).unwrap_err();
+            assert!(error.to_string().contains("not an ordering"), "{error}");
+        }
+    }
+}
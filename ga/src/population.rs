@@ -1,45 +1,433 @@
+use std::{collections::BTreeMap, fmt, hash::Hash, path::Path, time::Instant};
+
 use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use crate::traits::{Crossover, Fitness, FitnessRetrieve, Generate, Mutate};
+#[cfg(feature = "parallel")]
+use crate::parallel::par_map_seeded;
+use crate::{
+    cache::FitnessCache,
+    checkpoint::{Checkpoint, CheckpointError, CheckpointFormat, CHECKPOINT_VERSION},
+    crossover::ArrayCrossover,
+    diversity::{self, Diversity, DiversityMetric},
+    gp::TreeMutation,
+    niching::{self, Niching},
+    noise::{NoiseConfig, RunningStats},
+    objective::Objective,
+    observer::{Control, Observer, Observers, Operator},
+    parallel::map_seeded,
+    permutation::{PermutationCrossover, PermutationMutation},
+    real_vector::{RealCrossover, RealMutation},
+    replacement::{Replacement, ReplacementPolicy},
+    selection::{EmigrantSelection, Selection, SelectionMethod},
+    stats::{GenerationStats, History},
+    termination::{RestartConfig, RunProgress, RunReport, Termination},
+    traits::{
+        Crossover, Distance, Fitness, FitnessRetrieve, FitnessValue, Generate, Genes, Mutate,
+    },
+};
 
-#[derive(Debug, Default, Clone)]
-pub struct Genome<T: Clone + Default> {
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Genome<T: Clone + Default, F = f64> {
     pub data: T,
-    pub fitness: Option<f64>,
+    pub fitness: Option<F>,
+    /// Number of times a fitness has been recorded for `data`.
+    #[serde(default)]
+    pub evaluations: u64,
+}
+
+impl<T: Default + Clone, F> Default for Genome<T, F> {
+    fn default() -> Self {
+        Genome {
+            data: T::default(),
+            fitness: None,
+            evaluations: 0,
+        }
+    }
+}
+
+impl<T: Default + Clone, F> Genome<T, F> {
+    pub fn set_fitness(&mut self, fitness: Option<F>) {
+        self.fitness = fitness;
+        self.evaluations += 1;
+    }
 }
 
-impl<T: Default + Clone> FitnessRetrieve for Genome<T> {
-    fn get_fitness(&self) -> Option<f64> {
-        self.fitness
+impl<T: Default + Clone, F: FitnessValue> FitnessRetrieve for Genome<T, F> {
+    type Value = F;
+    fn get_fitness(&self) -> Option<F> {
+        self.fitness.clone()
+    }
+    fn set_fitness(&mut self, fitness: Option<F>) {
+        Genome::set_fitness(self, fitness);
     }
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct MutationConfig {
     pub gene_mutation_chance: f64,
+    #[serde(default)]
+    pub permutation: PermutationMutation,
+    #[serde(default)]
+    pub real: RealMutation,
+    #[serde(default)]
+    pub tree: TreeMutation,
+}
+
+/// `chance` limited to a probability for `Rng::gen_bool`, with NaN as 0.
+/// `PopulationConfig::validate` refuses such chances, but members can be bred
+/// from configs that were never checked.
+pub(crate) fn probability(chance: f64) -> f64 {
+    if chance.is_nan() {
+        0.0
+    } else {
+        chance.clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct CrossoverConfig {
+    pub array: ArrayCrossover,
+    #[serde(default)]
+    pub permutation: PermutationCrossover,
+    #[serde(default)]
+    pub real: RealCrossover,
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct PopulationConfig {
+    #[serde(default)]
     pub seed: [u8; 32],
     pub pop_size: usize,
     pub crossover_count: usize,
     pub mutate_count: usize,
     pub elitism_count: usize,
 
+    #[serde(default)]
     pub mutation_config: MutationConfig,
+    #[serde(default)]
+    pub crossover_config: CrossoverConfig,
+    #[serde(default)]
+    pub selection: SelectionMethod,
+    #[serde(default)]
+    pub objective: Objective,
+    /// Averaging for noisy fitness functions. `None` evaluates each member once.
+    #[serde(default)]
+    pub noise: Option<NoiseConfig>,
+    #[serde(default)]
+    pub replacement: Replacement,
+    /// Niching for parent selection. Needs a distance; see
+    /// `Population::set_distance`.
+    #[serde(default)]
+    pub niching: Option<Niching>,
+    #[serde(default)]
+    pub restart: Option<RestartConfig>,
+}
+
+impl PopulationConfig {
+    pub fn builder() -> PopulationConfigBuilder {
+        PopulationConfigBuilder::default()
+    }
+
+    /// Checks the invariants `Population` relies on.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.pop_size < 2 {
+            return Err(ConfigError::PopulationTooSmall {
+                pop_size: self.pop_size,
+            });
+        }
+        if let Replacement::SteadyState { offspring, policy } = self.replacement {
+            let replaceable = self.pop_size.saturating_sub(self.elitism_count);
+            if offspring == 0 || offspring > replaceable {
+                return Err(ConfigError::InvalidSteadyState {
+                    offspring,
+                    replaceable,
+                });
+            }
+            if !policy.is_valid() {
+                return Err(ConfigError::InvalidReplacementPolicy(policy));
+            }
+        }
+        let bred = self.elitism_count + self.mutate_count + self.crossover_count;
+        if self.replacement == Replacement::Generational && bred > self.pop_size {
+            return Err(ConfigError::TooManyOffspring {
+                bred,
+                pop_size: self.pop_size,
+            });
+        }
+        let crowding_offspring = self.mutate_count + self.crossover_count;
+        let window = match self.replacement {
+            Replacement::RestrictedTournament { window } => window,
+            _ => 1,
+        };
+        if self.replacement.is_crowding() && (crowding_offspring == 0 || window == 0) {
+            return Err(ConfigError::InvalidCrowding(self.replacement.clone()));
+        }
+        let chance = self.mutation_config.gene_mutation_chance;
+        if !(0.0..=1.0).contains(&chance) {
+            return Err(ConfigError::InvalidMutationChance(chance));
+        }
+        match self.crossover_config.array {
+            ArrayCrossover::Uniform { bias } if !(0.0..=1.0).contains(&bias) => {
+                return Err(ConfigError::InvalidCrossoverBias(bias));
+            }
+            ArrayCrossover::KPoint { points: 0 } => {
+                return Err(ConfigError::InvalidCrossoverPoints(0));
+            }
+            _ => {}
+        }
+        if !self.selection.is_valid() {
+            return Err(ConfigError::InvalidSelection(self.selection.clone()));
+        }
+        if let Some(noise) = self.noise.as_ref().filter(|n| n.samples == 0) {
+            return Err(ConfigError::InvalidNoiseSamples(noise.samples));
+        }
+        if let Some(niching) = self.niching.as_ref().filter(|n| !n.is_valid()) {
+            return Err(ConfigError::InvalidNiching(niching.clone()));
+        }
+        Ok(())
+    }
+}
+
+/// Builds a `PopulationConfig`, checking it with `PopulationConfig::validate`.
+/// Fields that are not set keep their `Default` values.
+#[derive(Debug, Default, Clone)]
+pub struct PopulationConfigBuilder {
+    config: PopulationConfig,
+}
+
+impl PopulationConfigBuilder {
+    pub fn seed(mut self, seed: [u8; 32]) -> Self {
+        self.config.seed = seed;
+        self
+    }
+    pub fn pop_size(mut self, pop_size: usize) -> Self {
+        self.config.pop_size = pop_size;
+        self
+    }
+    pub fn crossover_count(mut self, crossover_count: usize) -> Self {
+        self.config.crossover_count = crossover_count;
+        self
+    }
+    pub fn mutate_count(mut self, mutate_count: usize) -> Self {
+        self.config.mutate_count = mutate_count;
+        self
+    }
+    pub fn elitism_count(mut self, elitism_count: usize) -> Self {
+        self.config.elitism_count = elitism_count;
+        self
+    }
+    pub fn mutation_config(mut self, mutation_config: MutationConfig) -> Self {
+        self.config.mutation_config = mutation_config;
+        self
+    }
+    pub fn crossover_config(mut self, crossover_config: CrossoverConfig) -> Self {
+        self.config.crossover_config = crossover_config;
+        self
+    }
+    pub fn selection(mut self, selection: SelectionMethod) -> Self {
+        self.config.selection = selection;
+        self
+    }
+    pub fn objective(mut self, objective: Objective) -> Self {
+        self.config.objective = objective;
+        self
+    }
+    pub fn noise(mut self, noise: NoiseConfig) -> Self {
+        self.config.noise = Some(noise);
+        self
+    }
+    pub fn replacement(mut self, replacement: Replacement) -> Self {
+        self.config.replacement = replacement;
+        self
+    }
+    pub fn niching(mut self, niching: Niching) -> Self {
+        self.config.niching = Some(niching);
+        self
+    }
+    pub fn restart(mut self, restart: RestartConfig) -> Self {
+        self.config.restart = Some(restart);
+        self
+    }
+
+    pub fn build(self) -> Result<PopulationConfig, ConfigError> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    /// Crossover needs at least two members to choose from.
+    PopulationTooSmall { pop_size: usize },
+    /// `elitism_count + mutate_count + crossover_count` is larger than `pop_size`,
+    /// which would grow the population every generation.
+    TooManyOffspring { bred: usize, pop_size: usize },
+    /// `gene_mutation_chance` is not a probability.
+    InvalidMutationChance(f64),
+    /// The `bias` of uniform array crossover is not a probability.
+    InvalidCrossoverBias(f64),
+    /// Steady-state mode must replace at least one member per tick, and no
+    /// more than there are members outside the elites.
+    InvalidSteadyState {
+        offspring: usize,
+        replaceable: usize,
+    },
+    /// Niching radii and thresholds must be positive, and clearing capacity
+    /// at least 1.
+    InvalidNiching(Niching),
+    /// Crowding needs a `mutate_count` or `crossover_count` above 0, and
+    /// restricted tournaments a window of at least 1.
+    InvalidCrowding(Replacement),
+    /// Tournaments need at least one entrant, and truncation a proportion in
+    /// `(0, 1]`.
+    InvalidSelection(SelectionMethod),
+    /// K-point crossover needs at least one point.
+    InvalidCrossoverPoints(usize),
+    /// Noise handling needs at least one sample per evaluation.
+    InvalidNoiseSamples(usize),
+    /// Replacement tournaments need at least one entrant.
+    InvalidReplacementPolicy(ReplacementPolicy),
+    /// Niching or crowding is configured, but the population has no distance.
+    MissingDistance,
+    /// A condition checks a `Diversity` measure the population can't take.
+    UnmeasuredDiversity(DiversityMetric),
+    /// The restart trigger has a target the fitness type can't be built from.
+    InvalidRestartTarget,
+    /// Roulette and stochastic universal selection weigh members by
+    /// `FitnessValue::to_f64`, which only covers part of a tuple fitness.
+    ProportionalSelection(SelectionMethod),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::PopulationTooSmall { pop_size } => {
+                write!(f, "pop_size is {}, but must be at least 2", pop_size)
+            }
+            ConfigError::TooManyOffspring { bred, pop_size } => write!(
+                f,
+                "elitism, mutation and crossover counts add up to {}, more than pop_size {}",
+                bred, pop_size
+            ),
+            ConfigError::InvalidMutationChance(chance) => write!(
+                f,
+                "gene_mutation_chance is {}, but must be between 0 and 1",
+                chance
+            ),
+            ConfigError::InvalidCrossoverBias(bias) => write!(
+                f,
+                "uniform crossover bias is {}, but must be between 0 and 1",
+                bias
+            ),
+            ConfigError::InvalidSteadyState {
+                offspring,
+                replaceable,
+            } => write!(
+                f,
+                "steady-state offspring is {}, but must be between 1 and the {} non-elite members",
+                offspring, replaceable
+            ),
+            ConfigError::InvalidNiching(niching) => write!(
+                f,
+                "{:?} needs positive radii and thresholds and a capacity of at least 1",
+                niching
+            ),
+            ConfigError::InvalidCrowding(replacement) => write!(
+                f,
+                "{:?} needs offspring to breed and a window of at least 1",
+                replacement
+            ),
+            ConfigError::InvalidSelection(selection) => write!(
+                f,
+                "{:?} needs a tournament of at least 1 and a proportion above 0 and at most 1",
+                selection
+            ),
+            ConfigError::InvalidCrossoverPoints(points) => write!(
+                f,
+                "k-point crossover has {} points, but needs at least 1",
+                points
+            ),
+            ConfigError::InvalidNoiseSamples(samples) => write!(
+                f,
+                "noise handling draws {} samples, but needs at least 1",
+                samples
+            ),
+            ConfigError::InvalidReplacementPolicy(policy) => {
+                write!(f, "{:?} needs a tournament of at least 1", policy)
+            }
+            ConfigError::MissingDistance => write!(
+                f,
+                "niching and crowding need a distance; see Population::set_distance"
+            ),
+            ConfigError::UnmeasuredDiversity(metric) => write!(
+                f,
+                "{:?} is not measured; see Population::set_distance and Population::use_gene_entropy",
+                metric
+            ),
+            ConfigError::InvalidRestartTarget => write!(
+                f,
+                "the restart trigger's target can't be converted to the fitness type"
+            ),
+            ConfigError::ProportionalSelection(selection) => write!(
+                f,
+                "{:?} needs a fitness that is a single number, not a tuple",
+                selection
+            ),
+        }
+    }
 }
 
+impl std::error::Error for ConfigError {}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Population<T: Generate + Crossover + Mutate + Fitness + FitnessRetrieve + Default> {
     pub members: Vec<T>,
     pub config: PopulationConfig,
     generation: i64,
     seed: [u8; 32],
+    #[serde(default)]
+    evaluations: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    history: Option<History>,
+    /// Whether each member's fitness is up to date, in the same order as `members`.
+    /// Members past the end are treated as not evaluated.
+    #[serde(default)]
+    evaluated: Vec<bool>,
+    /// Fitness samples of each member when noise handling is enabled, in the
+    /// same order as `members`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    samples: Vec<RunningStats>,
+    /// Generation each member was created in, in steady-state mode, in the
+    /// same order as `members`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    born: Vec<i64>,
+    #[serde(skip)]
+    cache: Option<FitnessCache<T>>,
+    #[serde(skip)]
+    distance: Option<fn(&T, &T) -> f64>,
+    #[serde(skip)]
+    entropy: Option<fn(&[T]) -> f64>,
+    #[serde(skip)]
+    measure_diversity: bool,
+    /// Progress since the last restart, from the first tick on.
+    #[serde(skip)]
+    since_restart: Option<RunProgress<T::Value>>,
+    /// Evaluates batches of members; `None` evaluates them one after another.
+    #[serde(skip)]
+    evaluator: Option<Evaluator<T>>,
+    #[serde(skip)]
+    observers: Observers<T>,
+    #[serde(skip)]
+    stop_requested: bool,
 }
 
+/// Samples the fitness of each member in a batch with the seed at its
+/// position; see `sample_fitness`.
+type Evaluator<T> = fn(&mut [(T, RunningStats)], &[[u8; 32]], usize, bool);
+
 impl<T: Generate + Crossover + Mutate + Fitness + FitnessRetrieve + Default + Clone> Population<T> {
+    /// Generates the initial members. The config is not checked; see `try_new`.
     pub fn new(config: PopulationConfig) -> Population<T> {
         let mut rng: StdRng = SeedableRng::from_seed(config.seed);
         let mut members: Vec<T> = Vec::new();
@@ -51,15 +439,170 @@ impl<T: Generate + Crossover + Mutate + Fitness + FitnessRetrieve + Default + Cl
             members,
             config,
             generation: 1,
+            evaluations: 0,
+            history: None,
+            evaluated: Vec::new(),
+            samples: Vec::new(),
+            born: Vec::new(),
+            cache: None,
+            distance: None,
+            entropy: None,
+            evaluator: None,
+            measure_diversity: false,
+            since_restart: None,
+            observers: Observers::default(),
+            stop_requested: false,
+        }
+    }
+
+    /// Like `new`, but refuses configs that fail `PopulationConfig::validate`.
+    pub fn try_new(config: PopulationConfig) -> Result<Population<T>, ConfigError> {
+        config.validate()?;
+        Ok(Population::new(config))
+    }
+
+    /// Reuses the fitness of earlier members with an equal `key` instead of
+    /// evaluating duplicates. See `FitnessCache`.
+    pub fn enable_cache<K: Hash + Eq + 'static>(&mut self, key: fn(&T) -> K, capacity: usize)
+    where
+        T: 'static,
+    {
+        self.cache = Some(FitnessCache::new(key, capacity));
+    }
+
+    /// Sets the distance between members that niching and crowding
+    /// replacement use. See `use_genome_distance` for genomes that implement
+    /// `Distance`. The distance is not part of a checkpoint.
+    pub fn set_distance(&mut self, distance: fn(&T, &T) -> f64) {
+        self.distance = Some(distance);
+    }
+
+    /// Marks every member as not evaluated, so all of them are evaluated on the
+    /// next `tick`. Needed after changing `members` in place.
+    pub fn reset_evaluations(&mut self) {
+        self.evaluated.clear();
+    }
+
+    /// Registers an observer to be called on every following `tick`.
+    pub fn add_observer(&mut self, observer: impl Observer<T> + Send + 'static) {
+        self.observers.push(Box::new(observer));
+    }
+
+    /// True if an observer asked to stop at the end of the last `tick`.
+    pub fn stop_requested(&self) -> bool {
+        self.stop_requested
+    }
+
+    /// Adds `Diversity` to the stats of every following generation. Measuring
+    /// genotype distances takes one distance per pair of members.
+    pub fn enable_diversity(&mut self) {
+        self.measure_diversity = true;
+    }
+
+    /// Starts recording the stats of every following generation.
+    pub fn enable_history(&mut self) {
+        self.history.get_or_insert_with(History::default);
+    }
+
+    pub fn get_history(&self) -> Option<&History> {
+        self.history.as_ref()
+    }
+
+    /// Stats of the current members, without evaluating them.
+    pub fn current_stats(&self) -> GenerationStats {
+        let objective = self.config.objective;
+        let fitnesses: Vec<Option<T::Value>> =
+            self.members.iter().map(|m| m.get_fitness()).collect();
+        let best_interval = self.config.noise.as_ref().and_then(|noise| {
+            let best = (0..fitnesses.len())
+                .min_by(|a, b| objective.compare(&fitnesses[*a], &fitnesses[*b]))?;
+            self.samples.get(best)?.confidence_interval(noise.z)
+        });
+        let stats = GenerationStats::new(self.generation - 1, &fitnesses, objective);
+        GenerationStats {
+            total_evaluations: self.evaluations,
+            best_interval,
+            diversity: self
+                .measure_diversity
+                .then(|| self.diversity(&self.members)),
+            ..stats
+        }
+    }
+
+    pub(crate) fn measures_diversity(&self) -> bool {
+        self.measure_diversity
+    }
+
+    /// The diversity of `members`, which need not be this population's, with
+    /// its distance and gene entropy.
+    pub(crate) fn diversity(&self, members: &[T]) -> Diversity {
+        let pairwise = self
+            .distance
+            .map(|d| diversity::pairwise(members.len(), |i, j| d(&members[i], &members[j])));
+        Diversity {
+            mean_distance: pairwise.and_then(|p| p.0),
+            unique: pairwise.map(|p| p.1),
+            gene_entropy: self.entropy.map(|entropy| entropy(members)),
+        }
+    }
+
+    /// Replaces all but the best `keep` members with newly generated ones,
+    /// which are evaluated on the next `tick`.
+    pub fn restart(&mut self, keep: usize) {
+        self.sort_members();
+        let mut rng: StdRng = SeedableRng::from_seed(self.seed);
+        for i in keep.min(self.members.len())..self.members.len() {
+            self.members[i] = T::generate(rng.gen());
+            self.evaluated[i] = false;
+            if let Some(stats) = self.samples.get_mut(i) {
+                *stats = RunningStats::default();
+            }
+            if let Some(born) = self.born.get_mut(i) {
+                *born = self.generation;
+            }
         }
+        self.seed = rng.gen();
+    }
+
+    /// Fitness samples of each member, in the same order as `members`. Empty
+    /// unless noise handling is enabled.
+    pub fn get_samples(&self) -> &[RunningStats] {
+        &self.samples
+    }
+
+    pub fn get_generation(&self) -> i64 {
+        self.generation
     }
 
+    /// Number of `calculate_fitness` calls made so far.
+    pub fn get_evaluations(&self) -> u64 {
+        self.evaluations
+    }
+
+    /// Sorts best first according to the configured `Objective`.
     pub fn sort_members(&mut self) {
-        self.members.sort_by(|a, b| {
-            b.get_fitness()
-                .partial_cmp(&a.get_fitness())
-                .unwrap_or(std::cmp::Ordering::Less)
+        let objective = self.config.objective;
+        self.evaluated.resize(self.members.len(), false);
+        if self.config.noise.is_some() {
+            self.samples
+                .resize(self.members.len(), RunningStats::default());
+        }
+        let mut order: Vec<usize> = (0..self.members.len()).collect();
+        order.sort_by(|a, b| {
+            objective.compare(
+                &self.members[*a].get_fitness(),
+                &self.members[*b].get_fitness(),
+            )
         });
+        self.members = permute(std::mem::take(&mut self.members), &order);
+        self.evaluated = order.iter().map(|i| self.evaluated[*i]).collect();
+        if !self.samples.is_empty() {
+            self.samples = order.iter().map(|i| self.samples[*i]).collect();
+        }
+        if !self.born.is_empty() {
+            self.born.resize(self.members.len(), 0);
+            self.born = order.iter().map(|i| self.born[*i]).collect();
+        }
     }
 
     pub fn get_best_member(&mut self) -> &T {
@@ -67,172 +610,1008 @@ impl<T: Generate + Crossover + Mutate + Fitness + FitnessRetrieve + Default + Cl
         &self.members[0]
     }
 
+    /// The best fitness among the members, without sorting them.
+    pub(crate) fn best_fitness(&self) -> Option<T::Value> {
+        let objective = self.config.objective;
+        self.members
+            .iter()
+            .fold(None, |best, m| objective.best(best, m.get_fitness()))
+    }
+
+    /// Checks the config with `PopulationConfig::validate`, which `new` and
+    /// checkpoints skip, and what it needs beyond the config: a distance for
+    /// niching and crowding, and a restart trigger it can measure and convert.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.config.validate()?;
+        let proportional = matches!(
+            self.config.selection,
+            SelectionMethod::Roulette | SelectionMethod::StochasticUniversal
+        );
+        if proportional && !T::Value::is_scalar() {
+            return Err(ConfigError::ProportionalSelection(
+                self.config.selection.clone(),
+            ));
+        }
+        let needs_distance = self.config.niching.is_some() || self.config.replacement.is_crowding();
+        if needs_distance && self.distance.is_none() {
+            return Err(ConfigError::MissingDistance);
+        }
+        if let Some(restart) = &self.config.restart {
+            self.check_measured(&restart.trigger)?;
+            if restart.trigger.to_fitness::<T::Value>().is_none() {
+                return Err(ConfigError::InvalidRestartTarget);
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the population can take every `Diversity` measure
+    /// `condition` checks.
+    pub(crate) fn check_measured<F>(&self, condition: &Termination<F>) -> Result<(), ConfigError> {
+        for metric in condition.diversity_metrics() {
+            let measured = match metric {
+                DiversityMetric::MeanDistance | DiversityMetric::Unique => self.distance.is_some(),
+                DiversityMetric::GeneEntropy => self.entropy.is_some(),
+            };
+            if !measured {
+                return Err(ConfigError::UnmeasuredDiversity(metric));
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluates the members and breeds the next generation, first restarting
+    /// if `PopulationConfig::restart` says so. In steady-state mode a tick
+    /// replaces only a few members, but is still counted as a generation.
+    /// Panics if `validate` fails; `tick_with_stats` returns the error instead.
     pub fn tick(&mut self) {
+        if let Err(error) = self.tick_with_stats() {
+            panic!("{}", error);
+        }
+    }
+
+    /// Like `tick`, and returns the stats of the next generation, or the error
+    /// `tick` panics with.
+    pub fn tick_with_stats(&mut self) -> Result<GenerationStats, ConfigError> {
+        self.validate()?;
+        self.restart_if_triggered();
+        let started = Instant::now();
         let mut rng: StdRng = SeedableRng::from_seed(self.seed);
-        let mut new_pop: Vec<T> = Vec::new();
+        self.observers
+            .generation_start(self.generation, &self.members);
 
-        self.members.iter_mut().for_each(|m| {
-            m.calculate_fitness(rng.gen());
-        });
+        // Only members that are not evaluated yet use their seed.
+        let seeds: Vec<[u8; 32]> = self.members.iter().map(|_| rng.gen()).collect();
+        self.evaluated.resize(self.members.len(), false);
+        if let Some(noise) = &self.config.noise {
+            self.samples
+                .resize(self.members.len(), RunningStats::default());
+            if noise.reevaluate_elites {
+                // The last generation's elites lead `members`.
+                self.evaluated
+                    .iter_mut()
+                    .take(self.config.elitism_count)
+                    .for_each(|e| *e = false);
+            }
+        }
+        let noisy = self.config.noise.is_some();
+        let (mut evaluated, mut cache_hits) = evaluate_pending(
+            &mut self.members,
+            &mut self.evaluated,
+            &seeds,
+            self.cache.as_mut().filter(|_| !noisy),
+            self.config
+                .noise
+                .as_ref()
+                .map(|n| (n, self.samples.as_mut_slice())),
+            self.evaluator,
+        );
         self.sort_members();
+        self.observers.evaluated(self.generation, &self.members);
+        let fitnesses: Vec<Option<T::Value>> =
+            self.members.iter().map(|m| m.get_fitness()).collect();
 
-        // Elitism first
-        new_pop.extend(
-            self.members
-                .iter()
-                .take(self.config.elitism_count)
-                .cloned()
-                .collect::<Vec<_>>(),
-        );
+        let (offspring_evaluated, offspring_hits) = match self.niching_pools(&fitnesses) {
+            Some(pools) if !pools.is_empty() => self.replace(&pools, &mut rng),
+            _ => self.replace(&[Pool::all(fitnesses, self.config.objective)], &mut rng),
+        };
+        evaluated += offspring_evaluated;
+        cache_hits += offspring_hits;
+        self.evaluations += evaluated;
+        self.generation += 1;
+        self.seed = rng.gen();
 
-        // Then mutation
-        (0..self.config.mutate_count).for_each(|_| {
-            let mutatable_member = self.members.choose(&mut rng);
-            if let Some(t) = mutatable_member {
-                let mut m = t.mutate(&self.config.mutation_config, rng.gen());
-                m.calculate_fitness(rng.gen());
-                new_pop.push(m);
-            }
-        });
+        let lookups = evaluated + cache_hits;
+        let stats = GenerationStats {
+            evaluations: evaluated,
+            cache_hits,
+            cache_hit_rate: (self.cache.is_some() && lookups > 0)
+                .then(|| cache_hits as f64 / lookups as f64),
+            duration: started.elapsed(),
+            ..self.current_stats()
+        };
+        if let Some(history) = self.history.as_mut() {
+            history.push(stats.clone());
+        }
+        let best = self.best_fitness();
+        if let Some(progress) = self.since_restart.as_mut() {
+            progress.update(&stats, best);
+        }
+        self.stop_requested = self.observers.generation_end(&stats, &self.members) == Control::Stop;
+        Ok(stats)
+    }
 
-        // Then crossover
-        (0..self.config.crossover_count).for_each(|_| {
-            let crossoverable_members: Vec<&T> =
-                self.members.choose_multiple(&mut rng, 2).collect();
-            let mut crossoverd_member =
-                crossoverable_members[0].crossover(crossoverable_members[1], rng.gen());
-            crossoverd_member.calculate_fitness(rng.gen());
-            new_pop.push(crossoverd_member);
+    /// Restarts with `PopulationConfig::restart` if its trigger is met by the
+    /// progress since the last restart.
+    fn restart_if_triggered(&mut self) {
+        let Some(restart) = self.config.restart.clone() else {
+            return;
+        };
+        // Refused by `validate` before a tick starts.
+        let Some(trigger) = restart.trigger.to_fitness::<T::Value>() else {
+            return;
+        };
+        if trigger.uses_diversity() {
+            self.enable_diversity();
+        }
+        let objective = self.config.objective;
+        let progress = match self.since_restart.take() {
+            Some(progress) => progress,
+            None => RunProgress::new(&self.current_stats(), self.best_fitness(), objective),
+        };
+        self.since_restart = Some(if trigger.check(&progress).is_some() {
+            self.restart(restart.keep);
+            RunProgress::new(&self.current_stats(), self.best_fitness(), objective)
+        } else {
+            progress
         });
+    }
 
-        // Then newly generated ones
-        (new_pop.len()..self.config.pop_size).for_each(|_| {
-            let mut generated_member = T::generate(rng.gen());
-            generated_member.calculate_fitness(rng.gen());
-            new_pop.push(generated_member);
-        });
+    /// Parent pools for the configured niching, or `None` without niching.
+    fn niching_pools(&self, fitnesses: &[Option<T::Value>]) -> Option<Vec<Pool<f64>>> {
+        let niching = self.config.niching.as_ref()?;
+        let distance = self.distance?;
+        let members = &self.members;
+        let distance = |i: usize, j: usize| distance(&members[i], &members[j]);
+        let goodness = niching::goodness(fitnesses, self.config.objective);
+        let everyone = |fitnesses: Vec<Option<f64>>| {
+            vec![Pool {
+                members: (0..members.len()).collect(),
+                fitnesses,
+                objective: Objective::Maximize,
+                weight: 1.0,
+            }]
+        };
+        Some(match *niching {
+            Niching::Sharing { sigma, alpha } => {
+                everyone(niching::share(&goodness, distance, sigma, alpha))
+            }
+            Niching::Clearing { sigma, capacity } => {
+                everyone(niching::clear(&goodness, distance, sigma, capacity))
+            }
+            Niching::Speciation { threshold } => {
+                niching::speciate(members.len(), distance, threshold)
+                    .into_iter()
+                    .map(|species| {
+                        let fitnesses: Vec<Option<f64>> =
+                            species.iter().map(|i| goodness[*i]).collect();
+                        let weight = fitnesses.iter().flatten().sum::<f64>() / species.len() as f64;
+                        Pool {
+                            members: species,
+                            fitnesses,
+                            objective: Objective::Maximize,
+                            weight,
+                        }
+                    })
+                    .collect()
+            }
+        })
+    }
 
-        self.members = new_pop;
-        self.generation += 1;
-        self.seed = rng.gen();
+    /// Breeds from `pools` as set by the configured `Replacement`. Returns the
+    /// evaluations and cache hits.
+    fn replace<F: FitnessValue>(&mut self, pools: &[Pool<F>], rng: &mut StdRng) -> (u64, u64) {
+        match self.config.replacement {
+            Replacement::Generational => self.replace_generation(pools, rng),
+            Replacement::SteadyState { offspring, policy } => {
+                self.replace_steady_state(offspring, policy, pools, rng)
+            }
+            Replacement::DeterministicCrowding
+            | Replacement::ProbabilisticCrowding
+            | Replacement::RestrictedTournament { .. } => match self.distance {
+                Some(distance) => self.replace_crowding(distance, pools, rng),
+                // Refused by `validate` before a tick starts.
+                None => (0, 0),
+            },
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use rand::{rngs::StdRng, Rng, SeedableRng};
+    /// Replaces the members with the elites followed by newly bred and evaluated
+    /// offspring. Returns the evaluations and cache hits.
+    fn replace_generation<F: FitnessValue>(
+        &mut self,
+        pools: &[Pool<F>],
+        rng: &mut StdRng,
+    ) -> (u64, u64) {
+        let mut new_pop: Vec<T> = Vec::new();
 
-    use super::{
-        Crossover, Fitness, FitnessRetrieve, Generate, Mutate, MutationConfig, Population,
-        PopulationConfig,
-    };
+        // Elitism first
+        self.members
+            .iter()
+            .take(self.config.elitism_count)
+            .enumerate()
+            .for_each(|(i, m)| {
+                self.observers.offspring(m, Operator::Elitism, &[i]);
+                new_pop.push(m.clone());
+            });
+        // Offspring are evaluated together once they have all been created.
+        let elites = new_pop.len();
+        let mut offspring_seeds: Vec<[u8; 32]> = Vec::new();
 
-    impl Mutate for i64 {
-        fn mutate(&self, _config: &MutationConfig, _seed: [u8; 32]) -> Self {
-            4
+        // Then mutation, crossover and newly generated ones
+        let operators = Pool::slots(pools, self.config.mutate_count)
+            .map(|pool| (Operator::Mutation, pool))
+            .chain(
+                Pool::slots(pools, self.config.crossover_count)
+                    .map(|pool| (Operator::Crossover, pool)),
+            );
+        for (operator, pool) in operators {
+            if let Some((offspring, _)) = self.breed(operator, &pools[pool], rng) {
+                offspring_seeds.push(rng.gen());
+                new_pop.push(offspring);
+            }
+        }
+        (new_pop.len()..self.config.pop_size).for_each(|_| {
+            if let Some((offspring, _)) = self.breed(Operator::Generated, &pools[0], rng) {
+                offspring_seeds.push(rng.gen());
+                new_pop.push(offspring);
+            }
+        });
+
+        let noisy = self.config.noise.is_some();
+        let mut new_evaluated = vec![true; elites];
+        new_evaluated.resize(new_pop.len(), false);
+        let mut new_samples = Vec::new();
+        if noisy {
+            new_samples = self.samples[..elites].to_vec();
+            new_samples.resize(new_pop.len(), RunningStats::default());
         }
+        let counts = evaluate_pending(
+            &mut new_pop[elites..],
+            &mut new_evaluated[elites..],
+            &offspring_seeds,
+            self.cache.as_mut().filter(|_| !noisy),
+            self.config
+                .noise
+                .as_ref()
+                .map(|n| (n, &mut new_samples[elites..])),
+            self.evaluator,
+        );
+
+        self.members = new_pop;
+        self.evaluated = new_evaluated;
+        self.samples = new_samples;
+        counts
     }
 
-    impl FitnessRetrieve for i64 {
-        fn get_fitness(&self) -> Option<f64> {
-            Some(5.0)
+    /// Breeds and evaluates `count` offspring, then puts each in place of a
+    /// member chosen by `policy`, skipping the elites. Returns the evaluations
+    /// and cache hits.
+    fn replace_steady_state<F: FitnessValue>(
+        &mut self,
+        count: usize,
+        policy: ReplacementPolicy,
+        pools: &[Pool<F>],
+        rng: &mut StdRng,
+    ) -> (u64, u64) {
+        let brood = self.breed_offspring(count, pools, rng);
+        let len = self.members.len();
+        self.born.resize(len, 0);
+        let mut available: Vec<bool> = (0..len).map(|i| i >= self.config.elitism_count).collect();
+        for (n, child) in brood.members.into_iter().enumerate() {
+            let Some(victim) = policy.choose(&available, &self.born, &brood.parents[n], rng) else {
+                break;
+            };
+            available[victim] = false;
+            self.put(victim, child, brood.samples.get(n).copied());
         }
+        brood.counts
     }
 
-    impl Fitness for i64 {
-        fn calculate_fitness(&mut self, _seed: [u8; 32]) -> Option<f64> {
-            Some(6.0)
+    /// Breeds and evaluates `mutate_count + crossover_count` offspring, each of
+    /// which then competes with its rival as set by the crowding `Replacement`.
+    /// Each parent is replaced at most once, so an offspring whose parents were
+    /// all taken by siblings is dropped. Returns the evaluations and cache hits.
+    fn replace_crowding<F: FitnessValue>(
+        &mut self,
+        distance: fn(&T, &T) -> f64,
+        pools: &[Pool<F>],
+        rng: &mut StdRng,
+    ) -> (u64, u64) {
+        let count = self.config.mutate_count + self.config.crossover_count;
+        let brood = self.breed_offspring(count, pools, rng);
+        let objective = self.config.objective;
+        // Members are sorted best first, so this is the worst fitness before
+        // any offspring arrive.
+        let floor = self.members.last().and_then(|m| m.get_fitness());
+        let mut replaced = vec![false; self.members.len()];
+        for (n, child) in brood.members.into_iter().enumerate() {
+            let candidates: Vec<usize> = match self.config.replacement {
+                Replacement::RestrictedTournament { window } => (0..window)
+                    .map(|_| rng.gen_range(0..self.members.len()))
+                    .collect(),
+                _ => brood.parents[n]
+                    .iter()
+                    .copied()
+                    .filter(|p| !replaced[*p])
+                    .collect(),
+            };
+            let Some(rival) = candidates.into_iter().min_by(|a, b| {
+                distance(&child, &self.members[*a]).total_cmp(&distance(&child, &self.members[*b]))
+            }) else {
+                continue;
+            };
+            let (fitness, rival_fitness) = (child.get_fitness(), self.members[rival].get_fitness());
+            let wins = match self.config.replacement {
+                Replacement::ProbabilisticCrowding if rival >= self.config.elitism_count => {
+                    let goodness =
+                        niching::goodness(&[fitness, rival_fitness, floor.clone()], objective);
+                    let (child, rival) = (goodness[0].unwrap_or(0.0), goodness[1].unwrap_or(0.0));
+                    let chance = if child + rival > 0.0 {
+                        child / (child + rival)
+                    } else {
+                        0.5
+                    };
+                    rng.gen::<f64>() < chance
+                }
+                _ => !objective.is_better(&rival_fitness, &fitness),
+            };
+            if wins {
+                replaced[rival] = true;
+                self.put(rival, child, brood.samples.get(n).copied());
+            }
         }
+        brood.counts
     }
 
-    impl Crossover for i64 {
-        fn crossover(&self, _other: &Self, _seed: [u8; 32]) -> Self {
-            2
+    /// Breeds `count` offspring from `pools`, choosing each operator by the odds
+    /// of `mutate_count` against `crossover_count`, and evaluates them.
+    fn breed_offspring<F: FitnessValue>(
+        &mut self,
+        count: usize,
+        pools: &[Pool<F>],
+        rng: &mut StdRng,
+    ) -> Brood<T> {
+        let (mutate, crossover) = (self.config.mutate_count, self.config.crossover_count);
+        let mut offspring: Vec<T> = Vec::new();
+        let mut parents: Vec<Vec<usize>> = Vec::new();
+        let mut seeds: Vec<[u8; 32]> = Vec::new();
+        for pool in Pool::slots(pools, count) {
+            let operator = if mutate + crossover == 0 {
+                Operator::Generated
+            } else if rng.gen_range(0..mutate + crossover) < mutate {
+                Operator::Mutation
+            } else {
+                Operator::Crossover
+            };
+            if let Some((child, child_parents)) = self.breed(operator, &pools[pool], rng) {
+                seeds.push(rng.gen());
+                offspring.push(child);
+                parents.push(child_parents);
+            }
+        }
+
+        let noisy = self.config.noise.is_some();
+        let mut offspring_evaluated = vec![false; offspring.len()];
+        let mut samples = vec![RunningStats::default(); offspring.len()];
+        let counts = evaluate_pending(
+            &mut offspring,
+            &mut offspring_evaluated,
+            &seeds,
+            self.cache.as_mut().filter(|_| !noisy),
+            self.config
+                .noise
+                .as_ref()
+                .map(|n| (n, samples.as_mut_slice())),
+            self.evaluator,
+        );
+        if !noisy {
+            samples.clear();
+        }
+        Brood {
+            members: offspring,
+            parents,
+            samples,
+            counts,
         }
     }
 
-    impl Generate for i64 {
-        fn generate(_seed: [u8; 32]) -> Self {
-            1
+    /// Puts an evaluated `member` in place of the one at `index`, with its
+    /// fitness samples when noise handling is enabled.
+    fn put(&mut self, index: usize, member: T, samples: Option<RunningStats>) {
+        self.members[index] = member;
+        self.evaluated[index] = true;
+        self.born.resize(self.members.len(), 0);
+        self.born[index] = self.generation;
+        if let Some(stats) = self.samples.get_mut(index) {
+            *stats = samples.unwrap_or_default();
         }
     }
 
-    #[test]
-    fn test_serialise() {
-        let config = PopulationConfig {
-            pop_size: 10,
-            crossover_count: 2,
-            mutate_count: 2,
-            elitism_count: 2,
-            mutation_config: MutationConfig {
-                gene_mutation_chance: 0.3,
-            },
-            seed: [1; 32],
+    /// Creates one member with `operator`, choosing parents from `pool` with the
+    /// configured selection method. Returns it with its parents' indices, or
+    /// `None` if no parent could be selected. A lone parent is crossed with
+    /// itself.
+    fn breed<F: FitnessValue>(
+        &mut self,
+        operator: Operator,
+        pool: &Pool<F>,
+        rng: &mut StdRng,
+    ) -> Option<(T, Vec<usize>)> {
+        let (offspring, parents) = match operator {
+            Operator::Mutation => {
+                let selected = pool.select(&self.config.selection, 1, rng);
+                let parent = &self.members[*selected.first()?];
+                (
+                    parent.mutate(&self.config.mutation_config, rng.gen()),
+                    selected,
+                )
+            }
+            Operator::Crossover => {
+                let parents = pool.select(&self.config.selection, 2, rng);
+                let first = *parents.first()?;
+                let second = parents.get(1).copied().unwrap_or(first);
+                if !self.members[first].can_cross(&self.members[second]) {
+                    self.observers.incompatible_crossover(&parents);
+                }
+                let offspring = self.members[first].crossover(
+                    &self.members[second],
+                    &self.config.crossover_config,
+                    rng.gen(),
+                );
+                (offspring, parents)
+            }
+            Operator::Generated => (T::generate(rng.gen()), Vec::new()),
+            Operator::Elitism => unreachable!("elites are copied, not bred"),
         };
-        let mut p: Population<i64> = Population::new(config);
-        p.tick();
-        p.tick();
+        self.observers.offspring(&offspring, operator, &parents);
+        Some((offspring, parents))
+    }
 
-        let json_string = serde_json::to_string(&p).unwrap();
-        assert_eq!("{\"members\":[1,1,4,4,2,2,1,1,1,1],\"config\":{\"seed\":[1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1],\"pop_size\":10,\"crossover_count\":2,\"mutate_count\":2,\"elitism_count\":2,\"mutation_config\":{\"gene_mutation_chance\":0.3}},\"generation\":3,\"seed\":[61,240,161,170,168,40,224,71,3,3,129,86,151,76,130,42,28,222,7,123,91,195,241,6,231,203,202,179,218,241,247,167]}", &json_string);
+    /// Copies `count` members picked by `emigrants` to send to another
+    /// population, with their fitness samples when noise handling is enabled.
+    pub(crate) fn emigrants(
+        &mut self,
+        emigrants: &EmigrantSelection,
+        count: usize,
+        seed: [u8; 32],
+    ) -> Vec<(T, Option<RunningStats>)> {
+        self.sort_members();
+        let picked: Vec<usize> = match emigrants {
+            EmigrantSelection::Best => (0..count.min(self.members.len())).collect(),
+            EmigrantSelection::Select(method) => {
+                let fitnesses: Vec<Option<T::Value>> =
+                    self.members.iter().map(|m| m.get_fitness()).collect();
+                method.select(&fitnesses, self.config.objective, count, seed)
+            }
+        };
+        picked
+            .into_iter()
+            .map(|i| (self.members[i].clone(), self.samples.get(i).copied()))
+            .collect()
     }
 
-    impl Mutate for i32 {
-        fn mutate(&self, _config: &MutationConfig, seed: [u8; 32]) -> Self {
-            let mut rng: StdRng = SeedableRng::from_seed(seed);
-            rng.gen()
+    /// Puts each immigrant in place of a member chosen by `policy`. The best
+    /// `elitism_count` members and earlier immigrants are never replaced.
+    /// Immigrants keep their fitness and are not evaluated again.
+    pub(crate) fn immigrate(
+        &mut self,
+        immigrants: Vec<(T, Option<RunningStats>)>,
+        policy: ReplacementPolicy,
+        rng: &mut StdRng,
+    ) {
+        self.sort_members();
+        self.born.resize(self.members.len(), 0);
+        let mut available: Vec<bool> = (0..self.members.len())
+            .map(|i| i >= self.config.elitism_count)
+            .collect();
+        for (member, samples) in immigrants {
+            let Some(victim) = policy.choose(&available, &self.born, &[], rng) else {
+                break;
+            };
+            self.put(victim, member, samples);
+            available[victim] = false;
         }
     }
 
-    impl FitnessRetrieve for i32 {
-        fn get_fitness(&self) -> Option<f64> {
-            Some(5.0)
+    /// Ticks until `termination` is met and reports the best member. Fails if
+    /// `validate` does, or if `termination` checks a `Diversity` measure the
+    /// population can't take.
+    pub fn run(
+        &mut self,
+        termination: &Termination<T::Value>,
+    ) -> Result<RunReport<T, T::Value>, ConfigError> {
+        self.validate()?;
+        self.check_measured(termination)?;
+        if termination.uses_diversity() {
+            self.enable_diversity();
         }
+        let objective = self.config.objective;
+        let mut progress = RunProgress::new(&self.current_stats(), self.best_fitness(), objective);
+        let reason = loop {
+            if let Some(reason) = termination.check(&progress) {
+                break reason;
+            }
+            let stats = self.tick_with_stats()?;
+            progress.update(&stats, self.best_fitness());
+            if self.stop_requested {
+                break Termination::ObserverStop;
+            }
+        };
+
+        Ok(RunReport {
+            reason,
+            best: self.get_best_member().clone(),
+            generations: self.generation - 1,
+            evaluations: self.evaluations,
+            elapsed: progress.started.elapsed(),
+        })
     }
+}
 
-    impl Fitness for i32 {
-        fn calculate_fitness(&mut self, seed: [u8; 32]) -> Option<f64> {
-            let mut rng: StdRng = SeedableRng::from_seed(seed);
-            Some(rng.gen())
+impl<
+        T: Generate
+            + Crossover
+            + Mutate
+            + Fitness
+            + FitnessRetrieve
+            + Default
+            + Clone
+            + Serialize
+            + DeserializeOwned,
+    > Population<T>
+{
+    pub fn to_checkpoint(&self, metadata: BTreeMap<String, String>) -> Checkpoint<T, T::Value> {
+        Checkpoint {
+            version: CHECKPOINT_VERSION,
+            metadata,
+            config: self.config.clone(),
+            members: self.members.clone(),
+            fitnesses: self.members.iter().map(|m| m.get_fitness()).collect(),
+            samples: self.samples.clone(),
+            born: self.born.clone(),
+            seed: self.seed,
+            generation: self.generation,
+            evaluations: self.evaluations,
+            history: self.history.clone(),
         }
     }
 
-    impl Crossover for i32 {
-        fn crossover(&self, _other: &Self, seed: [u8; 32]) -> Self {
-            let mut rng: StdRng = SeedableRng::from_seed(seed);
-            rng.gen()
+    /// Resumes a population. Members with a fitness are not evaluated again;
+    /// members whose serialized form leaves out the fitness get it back with
+    /// `set_fitness`. Observers, the fitness cache and the distance are not
+    /// part of a checkpoint and must be added again.
+    pub fn from_checkpoint(checkpoint: Checkpoint<T, T::Value>) -> Population<T> {
+        let mut members = checkpoint.members;
+        for (member, fitness) in members.iter_mut().zip(&checkpoint.fitnesses) {
+            if member.get_fitness().is_none() && fitness.is_some() {
+                member.set_fitness(fitness.clone());
+            }
+        }
+        let evaluated = members
+            .iter()
+            .zip(&checkpoint.fitnesses)
+            .map(|(m, f)| f.is_some() && m.get_fitness().is_some())
+            .collect();
+        Population {
+            members,
+            config: checkpoint.config,
+            generation: checkpoint.generation,
+            seed: checkpoint.seed,
+            evaluations: checkpoint.evaluations,
+            history: checkpoint.history,
+            evaluated,
+            samples: checkpoint.samples,
+            born: checkpoint.born,
+            cache: None,
+            distance: None,
+            entropy: None,
+            evaluator: None,
+            measure_diversity: false,
+            since_restart: None,
+            observers: Observers::default(),
+            stop_requested: false,
         }
     }
 
-    impl Generate for i32 {
-        fn generate(seed: [u8; 32]) -> Self {
-            let mut rng: StdRng = SeedableRng::from_seed(seed);
-            rng.gen()
-        }
+    pub fn save_checkpoint(
+        &self,
+        path: impl AsRef<Path>,
+        format: CheckpointFormat,
+        metadata: BTreeMap<String, String>,
+    ) -> Result<(), CheckpointError> {
+        self.to_checkpoint(metadata).save(path, format)
     }
 
-    #[test]
-    fn test_deterministic() {
-        let config = PopulationConfig {
-            pop_size: 10,
-            crossover_count: 2,
-            mutate_count: 2,
-            elitism_count: 2,
-            mutation_config: MutationConfig {
-                gene_mutation_chance: 0.3,
+    /// Loads a population saved with `save_checkpoint`. Use `Checkpoint::load`
+    /// to read the metadata as well.
+    pub fn load_checkpoint(
+        path: impl AsRef<Path>,
+        format: CheckpointFormat,
+    ) -> Result<Population<T>, CheckpointError> {
+        Ok(Self::from_checkpoint(Checkpoint::load(path, format)?))
+    }
+}
+
+impl<T: Generate + Crossover + Mutate + Fitness + FitnessRetrieve + Default + Clone + Distance>
+    Population<T>
+{
+    /// Uses the genome's own `Distance` for niching, crowding and diversity.
+    pub fn use_genome_distance(&mut self) {
+        self.distance = Some(T::distance);
+    }
+}
+
+impl<T: Generate + Crossover + Mutate + Fitness + FitnessRetrieve + Default + Clone + Genes>
+    Population<T>
+{
+    /// Measures `Diversity::gene_entropy` from the genome's `Genes`.
+    pub fn use_gene_entropy(&mut self) {
+        self.entropy = Some(diversity::gene_entropy::<T>);
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<T: Generate + Crossover + Mutate + Fitness + FitnessRetrieve + Default + Clone + Send>
+    Population<T>
+{
+    /// Evaluates members across threads. Each member keeps its own seed, so
+    /// runs are the same as serial ones. Not part of a checkpoint.
+    pub fn use_parallel_evaluation(&mut self) {
+        self.evaluator = Some(par_sample_batch::<T>);
+    }
+}
+
+/// Evaluated offspring with their parents' indices and, when noise handling
+/// is enabled, their fitness samples.
+struct Brood<T> {
+    members: Vec<T>,
+    parents: Vec<Vec<usize>>,
+    samples: Vec<RunningStats>,
+    counts: (u64, u64),
+}
+
+/// Members that parents are chosen from, with the fitness selection sees for
+/// each, in the same order, and the pool's share of the offspring.
+struct Pool<F> {
+    members: Vec<usize>,
+    fitnesses: Vec<Option<F>>,
+    objective: Objective,
+    weight: f64,
+}
+
+impl<F: FitnessValue> Pool<F> {
+    /// Every member, with its own fitness.
+    fn all(fitnesses: Vec<Option<F>>, objective: Objective) -> Pool<F> {
+        Pool {
+            members: (0..fitnesses.len()).collect(),
+            fitnesses,
+            objective,
+            weight: 1.0,
+        }
+    }
+
+    /// The pool of each of `count` offspring, split by weight.
+    fn slots(pools: &[Pool<F>], count: usize) -> impl Iterator<Item = usize> {
+        let weights: Vec<f64> = pools.iter().map(|p| p.weight).collect();
+        niching::allocate(&weights, count)
+            .into_iter()
+            .enumerate()
+            .flat_map(|(pool, n)| std::iter::repeat_n(pool, n))
+    }
+
+    /// Member indices picked by `selection`. Uniform selection draws from `rng`
+    /// directly, as ticks did before selection was configurable, so seeded
+    /// runs with the default config are unchanged.
+    fn select(&self, selection: &SelectionMethod, count: usize, rng: &mut StdRng) -> Vec<usize> {
+        match selection {
+            SelectionMethod::Uniform if count == 1 => {
+                self.members.choose(rng).copied().into_iter().collect()
+            }
+            SelectionMethod::Uniform => self.members.choose_multiple(rng, count).copied().collect(),
+            _ => selection
+                .select(&self.fitnesses, self.objective, count, rng.gen())
+                .into_iter()
+                .map(|i| self.members[i])
+                .collect(),
+        }
+    }
+}
+
+/// Evaluates the members not marked in `evaluated`, each with the seed at its
+/// position. With a cache, duplicates of cached or pending members copy their
+/// fitness instead. With noise handling, each evaluation adds its samples to
+/// the member's running stats and sets the member's fitness to their mean.
+/// Returns the number of evaluations and cache hits.
+fn evaluate_pending<T: Fitness + Default + Clone>(
+    members: &mut [T],
+    evaluated: &mut [bool],
+    seeds: &[[u8; 32]],
+    mut cache: Option<&mut FitnessCache<T>>,
+    mut noise: Option<(&NoiseConfig, &mut [RunningStats])>,
+    evaluator: Option<Evaluator<T>>,
+) -> (u64, u64) {
+    let mut pending: Vec<usize> = Vec::new();
+    let mut hashes: Vec<u64> = Vec::new();
+    let mut duplicates: Vec<(usize, usize)> = Vec::new();
+    let mut cache_hits = 0;
+    for i in 0..members.len() {
+        if evaluated[i] {
+            continue;
+        }
+        let Some(cache) = cache.as_deref() else {
+            pending.push(i);
+            continue;
+        };
+        let hash = cache.hash(&members[i]);
+        if let Some(hit) = cache.get(hash, &members[i]) {
+            members[i] = hit.clone();
+            evaluated[i] = true;
+            cache_hits += 1;
+        } else if let Some(first) = (0..pending.len())
+            .find(|&n| hashes[n] == hash && cache.same_key(&members[pending[n]], &members[i]))
+        {
+            duplicates.push((i, pending[first]));
+            cache_hits += 1;
+        } else {
+            pending.push(i);
+            hashes.push(hash);
+        }
+    }
+
+    let mut batch: Vec<(T, RunningStats)> = pending
+        .iter()
+        .map(|i| {
+            let stats = noise
+                .as_ref()
+                .map_or_else(RunningStats::default, |(_, s)| s[*i]);
+            (std::mem::take(&mut members[*i]), stats)
+        })
+        .collect();
+    let batch_seeds: Vec<[u8; 32]> = pending.iter().map(|i| seeds[*i]).collect();
+    let sample_count = noise.as_ref().map_or(1, |(n, _)| n.samples.max(1));
+    let average = noise.is_some();
+    evaluator.unwrap_or(sample_batch)(&mut batch, &batch_seeds, sample_count, average);
+    for (n, (member, stats)) in batch.into_iter().enumerate() {
+        if let Some((_, samples)) = noise.as_mut() {
+            samples[pending[n]] = stats;
+        }
+        if let Some(cache) = cache.as_deref_mut() {
+            cache.insert(hashes[n], member.clone());
+        }
+        members[pending[n]] = member;
+        evaluated[pending[n]] = true;
+    }
+    for (i, first) in duplicates {
+        members[i] = members[first].clone();
+        evaluated[i] = true;
+    }
+    ((pending.len() * sample_count) as u64, cache_hits)
+}
+
+fn sample_batch<T: Fitness>(
+    batch: &mut [(T, RunningStats)],
+    seeds: &[[u8; 32]],
+    samples: usize,
+    average: bool,
+) {
+    map_seeded(batch, seeds, |(member, stats), seed| {
+        sample_fitness(member, stats, seed, samples, average)
+    });
+}
+
+#[cfg(feature = "parallel")]
+fn par_sample_batch<T: Fitness + Send>(
+    batch: &mut [(T, RunningStats)],
+    seeds: &[[u8; 32]],
+    samples: usize,
+    average: bool,
+) {
+    par_map_seeded(batch, seeds, |(member, stats), seed| {
+        sample_fitness(member, stats, seed, samples, average)
+    });
+}
+
+/// Calls `calculate_fitness` `samples` times, the first with `seed` and the
+/// rest with seeds drawn from it, and adds the valid results to `stats`.
+///
+/// With `average`, the member's fitness is then set to the mean of all its
+/// samples or, for fitness types without `FitnessValue::from_f64`, to the
+/// median of this evaluation's valid samples in the type's own order. A member
+/// whose samples are all invalid keeps its earlier valid fitness.
+fn sample_fitness<T: Fitness>(
+    member: &mut T,
+    stats: &mut RunningStats,
+    seed: [u8; 32],
+    samples: usize,
+    average: bool,
+) {
+    let previous = member.get_fitness().filter(|f| f.is_valid());
+    let mut rng: StdRng = SeedableRng::from_seed(seed);
+    let mut valid = Vec::new();
+    for n in 0..samples {
+        let seed = if n == 0 { seed } else { rng.gen() };
+        if let Some(fitness) = member.calculate_fitness(seed).filter(|f| f.is_valid()) {
+            stats.push(fitness.to_f64());
+            valid.push(fitness);
+        }
+    }
+    if !average {
+        return;
+    }
+    valid.sort_by(|a, b| a.compare(b));
+    let median = (!valid.is_empty()).then(|| valid.swap_remove(valid.len() / 2));
+    let fitness = stats
+        .mean()
+        .and_then(T::Value::from_f64)
+        .or(median)
+        .or(previous);
+    if fitness.is_some() {
+        member.set_fitness(fitness);
+    }
+}
+
+/// Reorders `items` so that position `i` holds the item previously at `order[i]`.
+fn permute<X>(items: Vec<X>, order: &[usize]) -> Vec<X> {
+    let mut slots: Vec<Option<X>> = items.into_iter().map(Some).collect();
+    order.iter().map(|i| slots[*i].take().unwrap()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::BTreeMap,
+        sync::{Arc, Mutex},
+    };
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    use serde::{Deserialize, Serialize};
+
+    use super::{
+        sample_fitness, ConfigError, Crossover, CrossoverConfig, Fitness, FitnessRetrieve,
+        Generate, Mutate, MutationConfig, Population, PopulationConfig, Termination,
+    };
+    use crate::{
+        checkpoint::{read_metadata, Checkpoint, CheckpointError, CheckpointFormat, TempPath},
+        crossover::ArrayCrossover,
+        diversity::{Diversity, DiversityMetric},
+        niching::Niching,
+        noise::{NoiseConfig, RunningStats},
+        objective::Objective,
+        observer::{Control, Observer, Operator},
+        problems::{OneMax, Sphere},
+        real_vector::{RealVector, RepairPolicy},
+        replacement::{Replacement, ReplacementPolicy},
+        selection::SelectionMethod,
+        stats::{GenerationStats, History},
+        termination::RestartConfig,
+        traits::Distance,
+    };
+
+    impl Mutate for i64 {
+        fn mutate(&self, _config: &MutationConfig, _seed: [u8; 32]) -> Self {
+            4
+        }
+    }
+
+    impl FitnessRetrieve for i64 {
+        type Value = f64;
+        fn get_fitness(&self) -> Option<f64> {
+            Some(5.0)
+        }
+        fn set_fitness(&mut self, _fitness: Option<f64>) {}
+    }
+
+    impl Fitness for i64 {
+        fn calculate_fitness(&mut self, _seed: [u8; 32]) -> Option<f64> {
+            Some(6.0)
+        }
+    }
+
+    impl Crossover for i64 {
+        fn crossover(&self, _other: &Self, _config: &CrossoverConfig, _seed: [u8; 32]) -> Self {
+            2
+        }
+    }
+
+    impl Generate for i64 {
+        fn generate(_seed: [u8; 32]) -> Self {
+            1
+        }
+    }
+
+    #[test]
+    fn test_serialise() {
+        let config = PopulationConfig {
+            pop_size: 10,
+            crossover_count: 2,
+            mutate_count: 2,
+            elitism_count: 2,
+            mutation_config: MutationConfig {
+                gene_mutation_chance: 0.3,
+                ..Default::default()
             },
             seed: [1; 32],
+            ..Default::default()
         };
         let mut p: Population<i64> = Population::new(config);
+        p.tick();
+        p.tick();
 
         let json_string = serde_json::to_string(&p).unwrap();
-        assert_eq!("{\"members\":[1,1,1,1,1,1,1,1,1,1],\"config\":{\"seed\":[1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1],\"pop_size\":10,\"crossover_count\":2,\"mutate_count\":2,\"elitism_count\":2,\"mutation_config\":{\"gene_mutation_chance\":0.3}},\"generation\":1,\"seed\":[61,119,195,211,231,165,151,165,122,239,25,225,34,155,137,19,36,226,231,187,28,137,64,231,241,187,37,96,44,109,235,7]}", &json_string);
+        assert_eq!("{\"members\":[1,1,4,4,2,2,1,1,1,1],\"config\":{\"seed\":[1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1],\"pop_size\":10,\"crossover_count\":2,\"mutate_count\":2,\"elitism_count\":2,\"mutation_config\":{\"gene_mutation_chance\":0.3,\"permutation\":\"Swap\",\"real\":{\"Gaussian\":{\"sigma\":0.1}},\"tree\":\"Subtree\"},\"crossover_config\":{\"array\":\"SinglePoint\",\"permutation\":\"Pmx\",\"real\":{\"Sbx\":{\"eta\":15.0}}},\"selection\":\"Uniform\",\"objective\":\"Maximize\",\"noise\":null,\"replacement\":\"Generational\",\"niching\":null,\"restart\":null},\"generation\":3,\"seed\":[61,240,161,170,168,40,224,71,3,3,129,86,151,76,130,42,28,222,7,123,91,195,241,6,231,203,202,179,218,241,247,167],\"evaluations\":26,\"evaluated\":[true,true,true,true,true,true,true,true,true,true]}", &json_string);
+    }
+
+    impl Mutate for i32 {
+        fn mutate(&self, _config: &MutationConfig, seed: [u8; 32]) -> Self {
+            let mut rng: StdRng = SeedableRng::from_seed(seed);
+            rng.gen()
+        }
+    }
+
+    impl FitnessRetrieve for i32 {
+        type Value = f64;
+        fn get_fitness(&self) -> Option<f64> {
+            Some(5.0)
+        }
+        fn set_fitness(&mut self, _fitness: Option<f64>) {}
+    }
+
+    impl Fitness for i32 {
+        fn calculate_fitness(&mut self, seed: [u8; 32]) -> Option<f64> {
+            let mut rng: StdRng = SeedableRng::from_seed(seed);
+            Some(rng.gen())
+        }
+    }
+
+    impl Crossover for i32 {
+        fn crossover(&self, _other: &Self, _config: &CrossoverConfig, seed: [u8; 32]) -> Self {
+            let mut rng: StdRng = SeedableRng::from_seed(seed);
+            rng.gen()
+        }
+    }
+
+    impl Generate for i32 {
+        fn generate(seed: [u8; 32]) -> Self {
+            let mut rng: StdRng = SeedableRng::from_seed(seed);
+            rng.gen()
+        }
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let config = PopulationConfig {
+            pop_size: 10,
+            crossover_count: 2,
+            mutate_count: 2,
+            elitism_count: 2,
+            mutation_config: MutationConfig {
+                gene_mutation_chance: 0.3,
+                ..Default::default()
+            },
+            seed: [1; 32],
+            ..Default::default()
+        };
+        let mut p: Population<i64> = Population::new(config);
+
+        let json_string = serde_json::to_string(&p).unwrap();
+        assert_eq!("{\"members\":[1,1,1,1,1,1,1,1,1,1],\"config\":{\"seed\":[1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1],\"pop_size\":10,\"crossover_count\":2,\"mutate_count\":2,\"elitism_count\":2,\"mutation_config\":{\"gene_mutation_chance\":0.3,\"permutation\":\"Swap\",\"real\":{\"Gaussian\":{\"sigma\":0.1}},\"tree\":\"Subtree\"},\"crossover_config\":{\"array\":\"SinglePoint\",\"permutation\":\"Pmx\",\"real\":{\"Sbx\":{\"eta\":15.0}}},\"selection\":\"Uniform\",\"objective\":\"Maximize\",\"noise\":null,\"replacement\":\"Generational\",\"niching\":null,\"restart\":null},\"generation\":1,\"seed\":[61,119,195,211,231,165,151,165,122,239,25,225,34,155,137,19,36,226,231,187,28,137,64,231,241,187,37,96,44,109,235,7],\"evaluations\":0,\"evaluated\":[]}", &json_string);
         p.tick();
         let json_string_saved = serde_json::to_string(&p).unwrap();
-        assert_eq!("{\"members\":[1,1,4,4,2,2,1,1,1,1],\"config\":{\"seed\":[1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1],\"pop_size\":10,\"crossover_count\":2,\"mutate_count\":2,\"elitism_count\":2,\"mutation_config\":{\"gene_mutation_chance\":0.3}},\"generation\":2,\"seed\":[62,237,20,223,252,169,243,175,40,214,53,17,190,190,202,51,248,78,220,247,106,111,146,223,129,95,220,120,28,166,42,182]}", &json_string_saved);
+        assert_eq!("{\"members\":[1,1,4,4,2,2,1,1,1,1],\"config\":{\"seed\":[1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1],\"pop_size\":10,\"crossover_count\":2,\"mutate_count\":2,\"elitism_count\":2,\"mutation_config\":{\"gene_mutation_chance\":0.3,\"permutation\":\"Swap\",\"real\":{\"Gaussian\":{\"sigma\":0.1}},\"tree\":\"Subtree\"},\"crossover_config\":{\"array\":\"SinglePoint\",\"permutation\":\"Pmx\",\"real\":{\"Sbx\":{\"eta\":15.0}}},\"selection\":\"Uniform\",\"objective\":\"Maximize\",\"noise\":null,\"replacement\":\"Generational\",\"niching\":null,\"restart\":null},\"generation\":2,\"seed\":[62,237,20,223,252,169,243,175,40,214,53,17,190,190,202,51,248,78,220,247,106,111,146,223,129,95,220,120,28,166,42,182],\"evaluations\":18,\"evaluated\":[true,true,true,true,true,true,true,true,true,true]}", &json_string_saved);
         p.tick();
         let json_string_third = serde_json::to_string(&p).unwrap();
-        assert_eq!("{\"members\":[1,1,4,4,2,2,1,1,1,1],\"config\":{\"seed\":[1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1],\"pop_size\":10,\"crossover_count\":2,\"mutate_count\":2,\"elitism_count\":2,\"mutation_config\":{\"gene_mutation_chance\":0.3}},\"generation\":3,\"seed\":[61,240,161,170,168,40,224,71,3,3,129,86,151,76,130,42,28,222,7,123,91,195,241,6,231,203,202,179,218,241,247,167]}", &json_string_third);
+        assert_eq!("{\"members\":[1,1,4,4,2,2,1,1,1,1],\"config\":{\"seed\":[1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1,1],\"pop_size\":10,\"crossover_count\":2,\"mutate_count\":2,\"elitism_count\":2,\"mutation_config\":{\"gene_mutation_chance\":0.3,\"permutation\":\"Swap\",\"real\":{\"Gaussian\":{\"sigma\":0.1}},\"tree\":\"Subtree\"},\"crossover_config\":{\"array\":\"SinglePoint\",\"permutation\":\"Pmx\",\"real\":{\"Sbx\":{\"eta\":15.0}}},\"selection\":\"Uniform\",\"objective\":\"Maximize\",\"noise\":null,\"replacement\":\"Generational\",\"niching\":null,\"restart\":null},\"generation\":3,\"seed\":[61,240,161,170,168,40,224,71,3,3,129,86,151,76,130,42,28,222,7,123,91,195,241,6,231,203,202,179,218,241,247,167],\"evaluations\":26,\"evaluated\":[true,true,true,true,true,true,true,true,true,true]}", &json_string_third);
 
         // Deserialise and test
         let mut p: Population<i64> = serde_json::from_str(&json_string_saved).unwrap();
@@ -240,4 +1619,1169 @@ mod tests {
         let json_string_third_again = serde_json::to_string(&p).unwrap();
         assert_eq!(json_string_third, json_string_third_again);
     }
+
+    /// The fitness is left out when serialized.
+    #[derive(Debug, Default, Clone, Serialize, Deserialize)]
+    struct Sampled(#[serde(skip)] Option<f64>);
+
+    impl Generate for Sampled {
+        fn generate(_seed: [u8; 32]) -> Self {
+            Sampled(None)
+        }
+    }
+
+    impl Mutate for Sampled {
+        fn mutate(&self, _config: &MutationConfig, _seed: [u8; 32]) -> Self {
+            Sampled(None)
+        }
+    }
+
+    impl Crossover for Sampled {
+        fn crossover(&self, _other: &Self, _config: &CrossoverConfig, _seed: [u8; 32]) -> Self {
+            Sampled(None)
+        }
+    }
+
+    impl Fitness for Sampled {
+        fn calculate_fitness(&mut self, seed: [u8; 32]) -> Option<f64> {
+            let mut rng: StdRng = SeedableRng::from_seed(seed);
+            self.0 = Some(rng.gen());
+            self.0
+        }
+    }
+
+    impl FitnessRetrieve for Sampled {
+        type Value = f64;
+        fn get_fitness(&self) -> Option<f64> {
+            self.0
+        }
+        fn set_fitness(&mut self, fitness: Option<f64>) {
+            self.0 = fitness;
+        }
+    }
+
+    #[test]
+    fn test_evaluation_seeds() {
+        // Fitness depends only on the evaluation seed, so this holds with and
+        // without parallel evaluation.
+        let config = PopulationConfig {
+            pop_size: 10,
+            crossover_count: 3,
+            mutate_count: 3,
+            elitism_count: 2,
+            seed: [2; 32],
+            ..Default::default()
+        };
+        let mut p: Population<Sampled> = Population::new(config);
+        (0..5).for_each(|_| p.tick());
+        let fitnesses: Vec<f64> = p.members.iter().flat_map(|m| m.get_fitness()).collect();
+        assert_eq!(
+            vec![
+                0.9943796694385885,
+                0.9748120411068134,
+                0.9901577526557924,
+                0.5064600996117855,
+                0.17803657231249015,
+                0.48456039015707053,
+                0.21786758687054386,
+                0.990312448934556,
+                0.9315502766894868,
+                0.792069584070058
+            ],
+            fitnesses
+        );
+
+        #[cfg(feature = "parallel")]
+        {
+            let mut parallel: Population<Sampled> = Population::new(p.config.clone());
+            parallel.use_parallel_evaluation();
+            (0..5).for_each(|_| parallel.tick());
+            let parallel_fitnesses: Vec<f64> = parallel
+                .members
+                .iter()
+                .flat_map(|m| m.get_fitness())
+                .collect();
+            assert_eq!(fitnesses, parallel_fitnesses);
+        }
+    }
+
+    /// Not `Send`, so it can't be evaluated across threads.
+    #[derive(Debug, Default, Clone)]
+    struct Shared(std::rc::Rc<i64>);
+
+    impl Generate for Shared {
+        fn generate(_seed: [u8; 32]) -> Self {
+            Shared(std::rc::Rc::new(1))
+        }
+    }
+
+    impl Mutate for Shared {
+        fn mutate(&self, _config: &MutationConfig, _seed: [u8; 32]) -> Self {
+            Shared(std::rc::Rc::new(*self.0 + 1))
+        }
+    }
+
+    impl Crossover for Shared {
+        fn crossover(&self, other: &Self, _config: &CrossoverConfig, _seed: [u8; 32]) -> Self {
+            Shared(std::rc::Rc::new((*self.0).max(*other.0)))
+        }
+    }
+
+    impl Fitness for Shared {
+        fn calculate_fitness(&mut self, _seed: [u8; 32]) -> Option<f64> {
+            Some(*self.0 as f64)
+        }
+    }
+
+    impl FitnessRetrieve for Shared {
+        type Value = f64;
+        fn get_fitness(&self) -> Option<f64> {
+            Some(*self.0 as f64)
+        }
+        fn set_fitness(&mut self, _fitness: Option<f64>) {}
+    }
+
+    #[test]
+    fn test_not_send() {
+        // Builds with every feature, since only parallel evaluation needs `Send`.
+        let mut p: Population<Shared> = Population::new(PopulationConfig {
+            pop_size: 4,
+            mutate_count: 2,
+            ..Default::default()
+        });
+        (0..3).for_each(|_| p.tick());
+        assert_eq!(4, p.get_generation());
+    }
+
+    #[test]
+    fn test_noise() {
+        let config = PopulationConfig {
+            pop_size: 10,
+            crossover_count: 3,
+            mutate_count: 3,
+            elitism_count: 2,
+            seed: [2; 32],
+            noise: Some(NoiseConfig {
+                samples: 8,
+                reevaluate_elites: true,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut p: Population<Sampled> = Population::new(config);
+        assert_eq!(144, p.tick_with_stats().unwrap().evaluations);
+        // Only the two elites and the eight offspring are sampled again.
+        let stats = p.tick_with_stats().unwrap();
+        assert_eq!(80, stats.evaluations);
+
+        let samples = p.get_samples();
+        assert_eq!(10, samples.len());
+        assert!(samples.iter().all(|s| s.count() > 0 && s.count() % 8 == 0));
+        p.members
+            .iter()
+            .zip(samples)
+            .for_each(|(m, s)| assert_eq!(s.mean(), m.get_fitness()));
+
+        let (lower, upper) = stats.best_interval.unwrap();
+        let best = stats.best.unwrap();
+        assert!(lower < best && best < upper);
+    }
+
+    /// Counts its evaluations; the fourth and later are invalid.
+    #[derive(Debug, Default, Clone)]
+    struct Drifting(Option<(u32, f64)>, u32);
+
+    impl FitnessRetrieve for Drifting {
+        type Value = (u32, f64);
+        fn get_fitness(&self) -> Option<(u32, f64)> {
+            self.0
+        }
+        fn set_fitness(&mut self, fitness: Option<(u32, f64)>) {
+            self.0 = fitness;
+        }
+    }
+
+    impl Fitness for Drifting {
+        fn calculate_fitness(&mut self, _seed: [u8; 32]) -> Option<(u32, f64)> {
+            self.1 += 1;
+            self.0 = Some((0, if self.1 < 4 { self.1 as f64 } else { f64::NAN }));
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_noise_without_from_f64() {
+        let mut member = Drifting::default();
+        let mut stats = RunningStats::default();
+        sample_fitness(&mut member, &mut stats, [0; 32], 4, true);
+        assert_eq!(3, stats.count());
+        assert_eq!(Some((0, 2.0)), member.get_fitness());
+
+        sample_fitness(&mut member, &mut stats, [1; 32], 4, true);
+        assert_eq!(3, stats.count());
+        assert_eq!(Some((0, 2.0)), member.get_fitness());
+    }
+
+    #[test]
+    fn test_steady_state() {
+        let config = PopulationConfig {
+            pop_size: 10,
+            crossover_count: 1,
+            mutate_count: 1,
+            elitism_count: 2,
+            seed: [2; 32],
+            replacement: Replacement::SteadyState {
+                offspring: 2,
+                policy: ReplacementPolicy::Worst,
+            },
+            ..Default::default()
+        };
+        let mut p: Population<Sampled> = Population::try_new(config.clone()).unwrap();
+        p.enable_history();
+        assert_eq!(12, p.tick_with_stats().unwrap().evaluations);
+        let stats = p.tick_with_stats().unwrap();
+        assert_eq!((2, 14), (stats.evaluations, stats.total_evaluations));
+        (0..20).for_each(|_| {
+            p.tick();
+        });
+        assert_eq!(10, p.members.len());
+        assert_eq!(22, p.get_generation() - 1);
+        // Replacing the worst never loses the best member.
+        assert!(best_never_drops(p.get_history().unwrap()));
+
+        for policy in [
+            ReplacementPolicy::Random,
+            ReplacementPolicy::Oldest,
+            ReplacementPolicy::TournamentLoser { size: 3 },
+            ReplacementPolicy::Parent,
+        ] {
+            let mut p: Population<Sampled> = Population::new(PopulationConfig {
+                replacement: Replacement::SteadyState {
+                    offspring: 3,
+                    policy,
+                },
+                ..config.clone()
+            });
+            (0..5).for_each(|_| {
+                p.tick();
+            });
+            assert_eq!(10, p.members.len(), "{:?}", policy);
+            assert_eq!(10 + 5 * 3, p.get_evaluations(), "{:?}", policy);
+        }
+    }
+
+    /// A OneMax config for the crowding, diversity and niching tests.
+    fn onemax_config(seed: u8) -> PopulationConfig {
+        PopulationConfig {
+            pop_size: 20,
+            crossover_count: 6,
+            mutate_count: 4,
+            elitism_count: 2,
+            mutation_config: MutationConfig {
+                gene_mutation_chance: 0.05,
+                ..Default::default()
+            },
+            selection: SelectionMethod::Tournament { size: 2 },
+            seed: [seed; 32],
+            ..Default::default()
+        }
+    }
+
+    /// True if the best fitness never got worse from one generation to the next.
+    fn best_never_drops(history: &History) -> bool {
+        let best: Vec<f64> = history.generations.iter().flat_map(|s| s.best).collect();
+        best.windows(2).all(|w| w[0] <= w[1])
+    }
+
+    /// The sorted members and the offspring of the latest tick.
+    #[derive(Default)]
+    struct Lineage {
+        sorted: Vec<OneMax>,
+        offspring: Vec<(OneMax, Vec<usize>)>,
+    }
+
+    struct LineageRecorder(Arc<Mutex<Lineage>>);
+
+    impl Observer<OneMax> for LineageRecorder {
+        fn on_evaluated(&mut self, _generation: i64, members: &[OneMax]) {
+            let mut lineage = self.0.lock().unwrap();
+            lineage.sorted = members.to_vec();
+            lineage.offspring.clear();
+        }
+
+        fn on_offspring(&mut self, offspring: &OneMax, _operator: Operator, parents: &[usize]) {
+            let mut lineage = self.0.lock().unwrap();
+            lineage
+                .offspring
+                .push((offspring.clone(), parents.to_vec()));
+        }
+    }
+
+    #[test]
+    fn test_crowding() {
+        for replacement in [
+            Replacement::DeterministicCrowding,
+            Replacement::ProbabilisticCrowding,
+            Replacement::RestrictedTournament { window: 4 },
+        ] {
+            let mut p: Population<OneMax> = Population::try_new(PopulationConfig {
+                replacement: replacement.clone(),
+                ..onemax_config(5)
+            })
+            .unwrap();
+            p.use_genome_distance();
+            p.enable_history();
+            p.run(&Termination::MaxGenerations(15)).unwrap();
+            assert_eq!(20, p.members.len(), "{:?}", replacement);
+            assert_eq!(20 + 15 * 10, p.get_evaluations(), "{:?}", replacement);
+            // Offspring only replace better rivals by chance, and never elites.
+            assert!(
+                best_never_drops(p.get_history().unwrap()),
+                "{:?}",
+                replacement
+            );
+        }
+
+        for replacement in [
+            Replacement::DeterministicCrowding,
+            Replacement::ProbabilisticCrowding,
+        ] {
+            let mut p: Population<OneMax> = Population::try_new(PopulationConfig {
+                replacement: replacement.clone(),
+                ..onemax_config(7)
+            })
+            .unwrap();
+            p.use_genome_distance();
+            let lineage = Arc::new(Mutex::new(Lineage::default()));
+            p.add_observer(LineageRecorder(lineage.clone()));
+            for _ in 0..10 {
+                let generation = p.get_generation();
+                p.tick();
+                let lineage = lineage.lock().unwrap();
+                let (old, new) = (&lineage.sorted, &p.members);
+                let replaced: Vec<bool> = (0..new.len())
+                    .map(|i| p.born.get(i) == Some(&generation))
+                    .collect();
+                if replacement == Replacement::DeterministicCrowding {
+                    // Each offspring competes with its nearest parent that no
+                    // sibling has replaced, and wins unless that parent is better.
+                    let mut expected = old.clone();
+                    let mut taken = vec![false; old.len()];
+                    for (child, parents) in &lineage.offspring {
+                        let fitness = child.clone().calculate_fitness([0; 32]);
+                        let rival =
+                            parents
+                                .iter()
+                                .copied()
+                                .filter(|j| !taken[*j])
+                                .min_by(|a, b| {
+                                    child
+                                        .distance(&old[*a])
+                                        .total_cmp(&child.distance(&old[*b]))
+                                });
+                        if let Some(rival) = rival.filter(|r| fitness >= old[*r].get_fitness()) {
+                            expected[rival] = child.clone();
+                            taken[rival] = true;
+                        }
+                    }
+                    assert_eq!(taken, replaced);
+                    assert!(expected.iter().zip(new).all(|(e, m)| e.distance(m) == 0.0));
+                } else {
+                    // Offspring only take their parents' places, and only beat
+                    // elites that are no better.
+                    for i in (0..new.len()).filter(|i| replaced[*i]) {
+                        assert!(lineage
+                            .offspring
+                            .iter()
+                            .any(|(child, parents)| parents.contains(&i)
+                                && child.distance(&new[i]) == 0.0));
+                        if i < p.config.elitism_count {
+                            assert!(new[i].get_fitness() >= old[i].get_fitness());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_diversity() {
+        let config = onemax_config(6);
+        let mut p: Population<OneMax> = Population::try_new(config.clone()).unwrap();
+        p.tick();
+        assert_eq!(None, p.current_stats().diversity);
+        p.enable_diversity();
+        assert_eq!(Some(Diversity::default()), p.current_stats().diversity);
+        let low = Termination::LowDiversity {
+            metric: DiversityMetric::MeanDistance,
+            threshold: 1.0,
+        };
+        assert_eq!(
+            Some(ConfigError::UnmeasuredDiversity(
+                DiversityMetric::MeanDistance
+            )),
+            p.run(&Termination::MaxGenerations(5).or(low)).err()
+        );
+        p.use_genome_distance();
+        let diversity = p.current_stats().diversity.unwrap();
+        assert!(diversity.mean_distance.unwrap() > 0.0);
+        assert!((1..=20).contains(&diversity.unique.unwrap()));
+
+        let plain = {
+            let mut p: Population<OneMax> = Population::try_new(config.clone()).unwrap();
+            p.run(&Termination::MaxGenerations(5)).unwrap()
+        };
+        // Fewer than 21 members are always unique, so every tick but the first
+        // starts from a restart that keeps the best two.
+        let restarting = |metric: DiversityMetric| PopulationConfig {
+            restart: Some(RestartConfig {
+                trigger: Termination::LowDiversity {
+                    metric,
+                    threshold: 21.0,
+                },
+                keep: 2,
+            }),
+            ..config.clone()
+        };
+        let mut p: Population<OneMax> =
+            Population::try_new(restarting(DiversityMetric::Unique)).unwrap();
+        p.use_genome_distance();
+        p.enable_history();
+        let report = p.run(&Termination::MaxGenerations(5)).unwrap();
+        assert_eq!(5, report.generations);
+        assert_eq!(plain.evaluations + 4 * 18, report.evaluations);
+        assert!(p.evaluated.iter().all(|e| *e));
+        assert!(best_never_drops(p.get_history().unwrap()));
+
+        // Ticking restarts just as running does.
+        let mut p: Population<OneMax> =
+            Population::try_new(restarting(DiversityMetric::Unique)).unwrap();
+        p.use_genome_distance();
+        (0..5).for_each(|_| p.tick());
+        assert_eq!(report.evaluations, p.get_evaluations());
+
+        // OneMax has no `Genes`, so its gene entropy is never measured.
+        let mut p: Population<OneMax> =
+            Population::try_new(restarting(DiversityMetric::GeneEntropy)).unwrap();
+        p.use_genome_distance();
+        let unmeasured = ConfigError::UnmeasuredDiversity(DiversityMetric::GeneEntropy);
+        assert_eq!(Err(unmeasured.clone()), p.validate());
+        assert_eq!(Some(unmeasured), p.tick_with_stats().err());
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let config = PopulationConfig::builder()
+            .pop_size(10)
+            .elitism_count(2)
+            .mutate_count(4)
+            .crossover_count(4)
+            .build()
+            .unwrap();
+        assert!(Population::<i64>::try_new(config.clone()).is_ok());
+
+        assert_eq!(
+            Some(ConfigError::TooManyOffspring {
+                bred: 11,
+                pop_size: 10
+            }),
+            PopulationConfig::builder()
+                .pop_size(10)
+                .elitism_count(3)
+                .mutate_count(4)
+                .crossover_count(4)
+                .build()
+                .err()
+        );
+        assert_eq!(
+            Some(ConfigError::InvalidSteadyState {
+                offspring: 9,
+                replaceable: 8
+            }),
+            PopulationConfig::builder()
+                .pop_size(10)
+                .elitism_count(2)
+                .replacement(Replacement::SteadyState {
+                    offspring: 9,
+                    policy: ReplacementPolicy::Worst
+                })
+                .build()
+                .err()
+        );
+        assert_eq!(
+            Some(ConfigError::PopulationTooSmall { pop_size: 1 }),
+            PopulationConfig::builder().pop_size(1).build().err()
+        );
+        let invalid = PopulationConfig {
+            mutation_config: MutationConfig {
+                gene_mutation_chance: 1.5,
+                ..Default::default()
+            },
+            ..config
+        };
+        assert_eq!(
+            Some(ConfigError::InvalidMutationChance(1.5)),
+            Population::<i64>::try_new(invalid).err()
+        );
+        let nan_bias = PopulationConfig::builder()
+            .pop_size(10)
+            .crossover_config(CrossoverConfig {
+                array: ArrayCrossover::Uniform { bias: f64::NAN },
+                ..Default::default()
+            })
+            .build();
+        assert!(matches!(
+            nan_bias,
+            Err(ConfigError::InvalidCrossoverBias(bias)) if bias.is_nan()
+        ));
+        let clearing = Niching::Clearing {
+            sigma: 1.0,
+            capacity: 0,
+        };
+        assert_eq!(
+            Some(ConfigError::InvalidNiching(clearing.clone())),
+            PopulationConfig::builder()
+                .pop_size(10)
+                .niching(clearing)
+                .build()
+                .err()
+        );
+        assert_eq!(
+            Some(ConfigError::InvalidCrowding(
+                Replacement::DeterministicCrowding
+            )),
+            PopulationConfig::builder()
+                .pop_size(10)
+                .replacement(Replacement::DeterministicCrowding)
+                .build()
+                .err()
+        );
+
+        let valid = PopulationConfig {
+            pop_size: 10,
+            ..Default::default()
+        };
+        for selection in [
+            SelectionMethod::Tournament { size: 0 },
+            SelectionMethod::Truncation { proportion: 0.0 },
+            SelectionMethod::Truncation { proportion: 1.5 },
+        ] {
+            assert_eq!(
+                Err(ConfigError::InvalidSelection(selection.clone())),
+                PopulationConfig {
+                    selection,
+                    ..valid.clone()
+                }
+                .validate()
+            );
+        }
+        assert!(matches!(
+            PopulationConfig {
+                selection: SelectionMethod::Truncation {
+                    proportion: f64::NAN
+                },
+                ..valid.clone()
+            }
+            .validate(),
+            Err(ConfigError::InvalidSelection(_))
+        ));
+        assert_eq!(
+            Err(ConfigError::InvalidCrossoverPoints(0)),
+            PopulationConfig {
+                crossover_config: CrossoverConfig {
+                    array: ArrayCrossover::KPoint { points: 0 },
+                    ..Default::default()
+                },
+                ..valid.clone()
+            }
+            .validate()
+        );
+        assert_eq!(
+            Err(ConfigError::InvalidNoiseSamples(0)),
+            PopulationConfig {
+                noise: Some(NoiseConfig {
+                    samples: 0,
+                    reevaluate_elites: false,
+                    z: 1.96,
+                }),
+                ..valid.clone()
+            }
+            .validate()
+        );
+        let loser = ReplacementPolicy::TournamentLoser { size: 0 };
+        assert_eq!(
+            Err(ConfigError::InvalidReplacementPolicy(loser)),
+            PopulationConfig {
+                mutate_count: 2,
+                replacement: Replacement::SteadyState {
+                    offspring: 2,
+                    policy: loser,
+                },
+                ..valid.clone()
+            }
+            .validate()
+        );
+        // Populations made without `try_new` are checked before they tick.
+        let crowding = PopulationConfig {
+            replacement: Replacement::ProbabilisticCrowding,
+            ..valid
+        };
+        let mut p: Population<OneMax> = Population::new(crowding.clone());
+        p.use_genome_distance();
+        assert_eq!(
+            Some(ConfigError::InvalidCrowding(crowding.replacement)),
+            p.tick_with_stats().err()
+        );
+    }
+
+    #[test]
+    fn test_niching() {
+        let config = PopulationConfig {
+            crossover_count: 8,
+            mutate_count: 8,
+            selection: SelectionMethod::Tournament { size: 3 },
+            ..onemax_config(4)
+        };
+        let run = |niching: Option<Niching>| {
+            let mut p: Population<OneMax> = Population::new(PopulationConfig {
+                niching,
+                ..config.clone()
+            });
+            p.use_genome_distance();
+            p.run(&Termination::MaxGenerations(10)).unwrap();
+            serde_json::to_string(&p.members).unwrap()
+        };
+
+        // Tournaments only compare, so a single species breeds as if there
+        // were no niching.
+        let plain = run(None);
+        assert_eq!(
+            plain,
+            run(Some(Niching::Speciation {
+                threshold: f64::INFINITY
+            }))
+        );
+        let speciated = run(Some(Niching::Speciation { threshold: 24.0 }));
+        assert_ne!(plain, speciated);
+        assert_eq!(
+            speciated,
+            run(Some(Niching::Speciation { threshold: 24.0 }))
+        );
+        run(Some(Niching::Sharing {
+            sigma: 16.0,
+            alpha: 1.0,
+        }));
+        run(Some(Niching::Clearing {
+            sigma: 8.0,
+            capacity: 2,
+        }));
+
+        let mut p: Population<OneMax> = Population::new(PopulationConfig {
+            niching: Some(Niching::Speciation { threshold: 24.0 }),
+            ..config
+        });
+        assert_eq!(Err(ConfigError::MissingDistance), p.validate());
+        assert_eq!(
+            Some(ConfigError::MissingDistance),
+            p.tick_with_stats().err()
+        );
+        assert_eq!(
+            Some(ConfigError::MissingDistance),
+            p.run(&Termination::MaxGenerations(3)).err()
+        );
+        assert_eq!((1, 0), (p.get_generation(), p.get_evaluations()));
+        p.use_genome_distance();
+        assert_eq!(Ok(()), p.validate());
+    }
+
+    #[test]
+    #[should_panic(expected = "niching and crowding need a distance")]
+    fn test_tick_without_distance() {
+        let mut p: Population<OneMax> = Population::new(PopulationConfig {
+            replacement: Replacement::DeterministicCrowding,
+            ..onemax_config(4)
+        });
+        p.tick();
+    }
+
+    #[test]
+    fn test_run() {
+        let config = PopulationConfig {
+            pop_size: 10,
+            crossover_count: 3,
+            mutate_count: 3,
+            elitism_count: 2,
+            seed: [2; 32],
+            ..Default::default()
+        };
+        let mut p: Population<Sampled> = Population::new(config);
+        let report = p
+            .run(&Termination::MaxGenerations(4).or(Termination::MaxEvaluations(25)))
+            .unwrap();
+        assert_eq!(Termination::MaxEvaluations(25), report.reason);
+        assert_eq!(2, report.generations);
+        assert_eq!(26, report.evaluations);
+
+        let report = p.run(&Termination::MaxGenerations(4)).unwrap();
+        assert_eq!(4, report.generations);
+        assert_eq!(p.get_best_member().get_fitness(), report.best.get_fitness());
+
+        let report = p.run(&Termination::TargetFitness(0.0)).unwrap();
+        assert_eq!(Termination::TargetFitness(0.0), report.reason);
+        assert_eq!(4, report.generations);
+    }
+
+    #[test]
+    fn test_minimize() {
+        let config = PopulationConfig {
+            pop_size: 10,
+            crossover_count: 3,
+            mutate_count: 3,
+            elitism_count: 2,
+            seed: [2; 32],
+            selection: SelectionMethod::Tournament { size: 3 },
+            objective: Objective::Minimize,
+            ..Default::default()
+        };
+        let mut p: Population<Sampled> = Population::new(config);
+        let report = p
+            .run(&Termination::MaxGenerations(5).or(Termination::TargetFitness(0.0)))
+            .unwrap();
+        assert_eq!(Termination::MaxGenerations(5), report.reason);
+
+        let stats = p.current_stats();
+        let fitnesses: Vec<f64> = p.members.iter().flat_map(|m| m.get_fitness()).collect();
+        let lowest = fitnesses.iter().copied().fold(f64::INFINITY, f64::min);
+        assert_eq!(Some(lowest), stats.best);
+        assert_eq!(Some(lowest), p.get_best_member().get_fitness());
+    }
+
+    /// Constraint violations first, then cost.
+    #[derive(Debug, Default, Clone)]
+    struct Constrained(Option<(u32, f64)>);
+
+    impl Generate for Constrained {
+        fn generate(_seed: [u8; 32]) -> Self {
+            Constrained(None)
+        }
+    }
+
+    impl Mutate for Constrained {
+        fn mutate(&self, _config: &MutationConfig, _seed: [u8; 32]) -> Self {
+            Constrained(None)
+        }
+    }
+
+    impl Crossover for Constrained {
+        fn crossover(&self, _other: &Self, _config: &CrossoverConfig, _seed: [u8; 32]) -> Self {
+            Constrained(None)
+        }
+    }
+
+    impl FitnessRetrieve for Constrained {
+        type Value = (u32, f64);
+        fn get_fitness(&self) -> Option<(u32, f64)> {
+            self.0
+        }
+        fn set_fitness(&mut self, fitness: Option<(u32, f64)>) {
+            self.0 = fitness;
+        }
+    }
+
+    impl Fitness for Constrained {
+        fn calculate_fitness(&mut self, seed: [u8; 32]) -> Option<(u32, f64)> {
+            if self.0.is_none() {
+                let mut rng: StdRng = SeedableRng::from_seed(seed);
+                self.0 = Some((rng.gen_range(0..3), rng.gen()));
+            }
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_lexicographic_fitness() {
+        let config = PopulationConfig {
+            pop_size: 12,
+            crossover_count: 3,
+            mutate_count: 3,
+            elitism_count: 3,
+            seed: [6; 32],
+            selection: SelectionMethod::Tournament { size: 2 },
+            objective: Objective::Minimize,
+            ..Default::default()
+        };
+        let mut p: Population<Constrained> = Population::new(config.clone());
+        (0..3).for_each(|_| {
+            p.tick();
+        });
+        p.sort_members();
+        let fitnesses: Vec<(u32, f64)> = p.members.iter().flat_map(|m| m.0).collect();
+        assert_eq!(12, fitnesses.len());
+        assert!(fitnesses
+            .windows(2)
+            .all(|w| w[0].0 < w[1].0 || (w[0].0 == w[1].0 && w[0].1 <= w[1].1)));
+        assert_eq!(Some(fitnesses[0]), p.best_fitness());
+
+        let p: Population<Constrained> = Population::new(PopulationConfig {
+            restart: Some(RestartConfig {
+                trigger: Termination::TargetFitness(0.5),
+                keep: 1,
+            }),
+            ..config.clone()
+        });
+        assert_eq!(Err(ConfigError::InvalidRestartTarget), p.validate());
+
+        let p: Population<Constrained> = Population::new(PopulationConfig {
+            selection: SelectionMethod::Roulette,
+            ..config.clone()
+        });
+        assert_eq!(
+            Err(ConfigError::ProportionalSelection(
+                SelectionMethod::Roulette
+            )),
+            p.validate()
+        );
+        let p: Population<Constrained> = Population::new(PopulationConfig {
+            selection: SelectionMethod::Rank,
+            ..config
+        });
+        assert_eq!(Ok(()), p.validate());
+    }
+
+    /// Only three distinct genomes exist, so most new members are duplicates.
+    #[derive(Debug, Default, Clone)]
+    struct Digit(u8, Option<f64>);
+
+    impl Generate for Digit {
+        fn generate(seed: [u8; 32]) -> Self {
+            let mut rng: StdRng = SeedableRng::from_seed(seed);
+            Digit(rng.gen_range(0..3), None)
+        }
+    }
+
+    impl Mutate for Digit {
+        fn mutate(&self, _config: &MutationConfig, seed: [u8; 32]) -> Self {
+            Digit::generate(seed)
+        }
+    }
+
+    impl Crossover for Digit {
+        fn crossover(&self, other: &Self, _config: &CrossoverConfig, _seed: [u8; 32]) -> Self {
+            Digit(other.0, None)
+        }
+    }
+
+    impl FitnessRetrieve for Digit {
+        type Value = f64;
+        fn get_fitness(&self) -> Option<f64> {
+            self.1
+        }
+        fn set_fitness(&mut self, fitness: Option<f64>) {
+            self.1 = fitness;
+        }
+    }
+
+    impl Fitness for Digit {
+        fn calculate_fitness(&mut self, _seed: [u8; 32]) -> Option<f64> {
+            self.1 = Some(self.0 as f64);
+            self.1
+        }
+    }
+
+    #[test]
+    fn test_cache() {
+        let config = PopulationConfig {
+            pop_size: 10,
+            crossover_count: 3,
+            mutate_count: 3,
+            elitism_count: 2,
+            seed: [4; 32],
+            ..Default::default()
+        };
+        let mut p: Population<Digit> = Population::new(config);
+        p.enable_cache(|d| d.0, 100);
+        let first = p.tick_with_stats().unwrap();
+        assert_eq!(3, first.evaluations);
+        assert_eq!(15, first.cache_hits);
+        assert_eq!(Some(15.0 / 18.0), first.cache_hit_rate);
+
+        let second = p.tick_with_stats().unwrap();
+        assert_eq!(0, second.evaluations);
+        assert_eq!(Some(1.0), second.cache_hit_rate);
+        assert_eq!(3, p.get_evaluations());
+        assert!(p.members.iter().all(|m| m.1 == Some(m.0 as f64)));
+    }
+
+    #[test]
+    fn test_history() {
+        let config = PopulationConfig {
+            pop_size: 10,
+            crossover_count: 3,
+            mutate_count: 3,
+            elitism_count: 2,
+            seed: [2; 32],
+            ..Default::default()
+        };
+        let mut p: Population<Sampled> = Population::new(config);
+        let first = p.tick_with_stats().unwrap();
+        assert_eq!(1, first.generation);
+        assert_eq!(18, first.evaluations);
+        assert!(p.get_history().is_none());
+
+        p.enable_history();
+        (0..3).for_each(|_| {
+            p.tick();
+        });
+        let best = p.get_best_member().get_fitness();
+        let history = p.get_history().unwrap();
+        assert_eq!(3, history.generations.len());
+        let last = &history.generations[2];
+        assert_eq!(4, last.generation);
+        assert_eq!(8, last.evaluations);
+        assert_eq!(42, last.total_evaluations);
+        assert_eq!(best, last.best);
+        assert_eq!(4, history.to_csv().lines().count());
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Arc<Mutex<Vec<String>>>,
+        stop_after: i64,
+    }
+
+    impl Observer<Sampled> for Recorder {
+        fn on_generation_start(&mut self, generation: i64, _members: &[Sampled]) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("start {}", generation));
+        }
+
+        fn on_evaluated(&mut self, _generation: i64, members: &[Sampled]) {
+            assert!(members.iter().all(|m| m.get_fitness().is_some()));
+            self.events.lock().unwrap().push("evaluated".to_string());
+        }
+
+        fn on_offspring(&mut self, _offspring: &Sampled, operator: Operator, parents: &[usize]) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("{:?} {}", operator, parents.len()));
+        }
+
+        fn on_generation_end(&mut self, stats: &GenerationStats, _members: &[Sampled]) -> Control {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("end {}", stats.generation));
+            if stats.generation >= self.stop_after {
+                Control::Stop
+            } else {
+                Control::Continue
+            }
+        }
+    }
+
+    #[test]
+    fn test_observer() {
+        let config = PopulationConfig {
+            pop_size: 6,
+            crossover_count: 1,
+            mutate_count: 2,
+            elitism_count: 1,
+            seed: [2; 32],
+            ..Default::default()
+        };
+        let mut p: Population<Sampled> = Population::new(config);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        p.add_observer(Recorder {
+            events: events.clone(),
+            stop_after: 2,
+        });
+        // Observers don't stop a population from moving between threads.
+        fn assert_send<X: Send>(_: &X) {}
+        assert_send(&p);
+
+        let report = p.run(&Termination::MaxGenerations(10)).unwrap();
+        assert_eq!(Termination::ObserverStop, report.reason);
+        assert_eq!(2, report.generations);
+        assert!(p.stop_requested());
+
+        let events = events.lock().unwrap();
+        assert_eq!(
+            vec![
+                "start 1",
+                "evaluated",
+                "Elitism 1",
+                "Mutation 1",
+                "Mutation 1",
+                "Crossover 2",
+                "Generated 0",
+                "Generated 0",
+                "end 1",
+            ],
+            events[..9]
+        );
+        assert_eq!(18, events.len());
+    }
+
+    #[derive(Default)]
+    struct Incompatible(Arc<Mutex<usize>>);
+
+    impl Observer<Sphere> for Incompatible {
+        fn on_incompatible_crossover(&mut self, parents: &[usize]) {
+            assert_eq!(2, parents.len());
+            *self.0.lock().unwrap() += 1;
+        }
+    }
+
+    #[test]
+    fn test_incompatible_crossover() {
+        let config = PopulationConfig {
+            pop_size: 4,
+            crossover_count: 4,
+            seed: [3; 32],
+            ..Default::default()
+        };
+        let mut p: Population<Sphere> = Population::new(config);
+        let count = Arc::new(Mutex::new(0));
+        p.add_observer(Incompatible(count.clone()));
+        p.tick();
+        assert_eq!(0, *count.lock().unwrap());
+
+        // Crossing the shorter member with any other can't combine them.
+        p.members[0] = Sphere(RealVector::generate_bounded(
+            vec![(0.0, 1.0); 2],
+            RepairPolicy::Clamp,
+            [4; 32],
+        ));
+        p.reset_evaluations();
+        p.tick();
+        assert!(*count.lock().unwrap() > 0);
+    }
+
+    /// Everything but timings, which differ between runs.
+    fn assert_same_state(a: &Population<i64>, b: &Population<i64>) {
+        let (a, b) = (
+            a.to_checkpoint(BTreeMap::new()),
+            b.to_checkpoint(BTreeMap::new()),
+        );
+        assert_eq!(a.members, b.members);
+        assert_eq!(a.seed, b.seed);
+        assert_eq!(a.generation, b.generation);
+        assert_eq!(a.evaluations, b.evaluations);
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        let config = PopulationConfig {
+            pop_size: 10,
+            crossover_count: 2,
+            mutate_count: 2,
+            elitism_count: 2,
+            seed: [1; 32],
+            ..Default::default()
+        };
+        let mut p: Population<i64> = Population::new(config);
+        p.enable_history();
+        p.tick();
+
+        let path = TempPath::new("checkpoint.json");
+        let metadata = BTreeMap::from([("problem".to_string(), "constant".to_string())]);
+        p.save_checkpoint(&path, CheckpointFormat::Json, metadata.clone())
+            .unwrap();
+        let checkpoint: Checkpoint<i64> = Checkpoint::load(&path, CheckpointFormat::Json).unwrap();
+        assert_eq!(metadata, checkpoint.metadata);
+        assert_eq!(
+            metadata,
+            read_metadata(&path, CheckpointFormat::Json).unwrap()
+        );
+        assert_eq!(vec![Some(5.0); 10], checkpoint.fitnesses);
+
+        let mut resumed: Population<i64> =
+            Population::load_checkpoint(&path, CheckpointFormat::Json).unwrap();
+        p.tick();
+        resumed.tick();
+        assert_same_state(&p, &resumed);
+        assert_eq!(2, resumed.get_history().unwrap().generations.len());
+
+        let json = String::from_utf8(checkpoint.to_bytes(CheckpointFormat::Json).unwrap())
+            .unwrap()
+            .replacen("\"version\":3", "\"version\":99", 1);
+        match Checkpoint::<i64>::from_bytes(json.as_bytes(), CheckpointFormat::Json) {
+            Err(CheckpointError::UnsupportedVersion { found, supported }) => {
+                assert_eq!((99, 3), (found, supported))
+            }
+            other => panic!("expected a version error, got {:?}", other),
+        }
+
+        // Version 1 had neither noise samples nor birth generations.
+        let mut v1: serde_json::Value =
+            serde_json::from_slice(&checkpoint.to_bytes(CheckpointFormat::Json).unwrap()).unwrap();
+        let fields = v1.as_object_mut().unwrap();
+        fields.insert("version".to_string(), 1.into());
+        fields.remove("samples");
+        fields.remove("born");
+        let old: Checkpoint<i64> =
+            Checkpoint::from_bytes(v1.to_string().as_bytes(), CheckpointFormat::Json).unwrap();
+        assert!(old.samples.is_empty() && old.born.is_empty());
+        assert_eq!(checkpoint.members, old.members);
+    }
+
+    #[test]
+    fn test_checkpoint_fitness() {
+        let mut p: Population<Sampled> = Population::new(PopulationConfig {
+            pop_size: 6,
+            crossover_count: 2,
+            mutate_count: 2,
+            elitism_count: 1,
+            seed: [4; 32],
+            ..Default::default()
+        });
+        p.tick();
+        let bytes = p
+            .to_checkpoint(BTreeMap::new())
+            .to_bytes(CheckpointFormat::Json)
+            .unwrap();
+        let mut resumed = Population::from_checkpoint(
+            Checkpoint::<Sampled>::from_bytes(&bytes, CheckpointFormat::Json).unwrap(),
+        );
+        // JSON may round the last digit of a float.
+        let same_fitness = |a: &Population<Sampled>, b: &Population<Sampled>| {
+            a.members.iter().zip(&b.members).all(|(a, b)| {
+                let (a, b) = (a.get_fitness().unwrap(), b.get_fitness().unwrap());
+                (a - b).abs() < 1e-12
+            })
+        };
+        assert!(same_fitness(&p, &resumed));
+        p.tick();
+        resumed.tick();
+        assert_eq!(p.get_evaluations(), resumed.get_evaluations());
+        assert!(same_fitness(&p, &resumed));
+    }
+
+    #[cfg(feature = "binary")]
+    #[test]
+    fn test_binary_checkpoint() {
+        let mut p: Population<i64> = Population::new(PopulationConfig {
+            pop_size: 4,
+            seed: [1; 32],
+            ..Default::default()
+        });
+        p.enable_history();
+        p.tick();
+        p.enable_diversity();
+        p.tick();
+        let bytes = p
+            .to_checkpoint(BTreeMap::new())
+            .to_bytes(CheckpointFormat::Binary)
+            .unwrap();
+        let resumed = Population::from_checkpoint(
+            Checkpoint::<i64>::from_bytes(&bytes, CheckpointFormat::Binary).unwrap(),
+        );
+        assert_same_state(&p, &resumed);
+        // Generations with and without diversity both survive the format.
+        assert_eq!(p.get_history(), resumed.get_history());
+        assert!(bytes.len() < serde_json::to_vec(&p).unwrap().len());
+    }
 }
@@ -0,0 +1,130 @@
+//! Benchmark problems registered by `Registry::default()`.
+//!
+//! | Name        | Genome                       | Objective |
+//! |-------------|------------------------------|-----------|
+//! | `onemax`    | 64 bits, counts the ones     | Maximize  |
+//! | `sphere`    | 10 reals in ±5.12, Σx²       | Minimize  |
+//! | `rastrigin` | 10 reals in ±5.12, Rastrigin | Minimize  |
+//!
+//! Experiments must use the problem's objective; a population config asking
+//! for the other direction is refused with `ExperimentError::Objective`.
+
+use std::f64::consts::PI;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bit_string::BitString,
+    experiment::{run_problem, Registry},
+    objective::Objective,
+    population::{CrossoverConfig, MutationConfig},
+    real_vector::{RealVector, RepairPolicy},
+    traits::{Crossover, Distance, Fitness, FitnessRetrieve, Generate, Mutate},
+};
+
+pub const DIMENSIONS: usize = 10;
+pub const BOUNDS: (f64, f64) = (-5.12, 5.12);
+
+/// Adds the problems of this module to `registry`.
+pub fn register(registry: &mut Registry) {
+    registry.register("onemax", Objective::Maximize, run_problem::<OneMax>);
+    registry.register("sphere", Objective::Minimize, run_problem::<Sphere>);
+    registry.register("rastrigin", Objective::Minimize, run_problem::<Rastrigin>);
+}
+
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct OneMax(pub BitString);
+
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct Sphere(pub RealVector);
+
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct Rastrigin(pub RealVector);
+
+/// Delegates the operators, distance and fitness storage to the wrapped genome.
+macro_rules! wrapped_genome {
+    ($($name:ident),*) => {$(
+        impl Mutate for $name {
+            fn mutate(&self, config: &MutationConfig, seed: [u8; 32]) -> Self {
+                $name(self.0.mutate(config, seed))
+            }
+        }
+
+        impl Crossover for $name {
+            fn crossover(&self, other: &Self, config: &CrossoverConfig, seed: [u8; 32]) -> Self {
+                $name(self.0.crossover(&other.0, config, seed))
+            }
+            fn can_cross(&self, other: &Self) -> bool {
+                self.0.can_cross(&other.0)
+            }
+        }
+
+        impl Distance for $name {
+            fn distance(&self, other: &Self) -> f64 {
+                self.0.distance(&other.0)
+            }
+        }
+
+        impl FitnessRetrieve for $name {
+            type Value = f64;
+            fn get_fitness(&self) -> Option<f64> {
+                self.0.get_fitness()
+            }
+            fn set_fitness(&mut self, fitness: Option<f64>) {
+                self.0.set_fitness(fitness);
+            }
+        }
+    )*};
+}
+
+wrapped_genome!(OneMax, Sphere, Rastrigin);
+
+impl Generate for OneMax {
+    fn generate(seed: [u8; 32]) -> Self {
+        OneMax(BitString::generate(seed))
+    }
+}
+
+impl Fitness for OneMax {
+    fn calculate_fitness(&mut self, _seed: [u8; 32]) -> Option<f64> {
+        self.0.set_fitness(Some(self.0.count_ones() as f64));
+        self.0.get_fitness()
+    }
+}
+
+fn bounded(seed: [u8; 32]) -> RealVector {
+    RealVector::generate_bounded(vec![BOUNDS; DIMENSIONS], RepairPolicy::Clamp, seed)
+}
+
+impl Generate for Sphere {
+    fn generate(seed: [u8; 32]) -> Self {
+        Sphere(bounded(seed))
+    }
+}
+
+impl Fitness for Sphere {
+    fn calculate_fitness(&mut self, _seed: [u8; 32]) -> Option<f64> {
+        let value = self.0.get_data().iter().map(|x| x * x).sum();
+        self.0.set_fitness(Some(value));
+        self.0.get_fitness()
+    }
+}
+
+impl Generate for Rastrigin {
+    fn generate(seed: [u8; 32]) -> Self {
+        Rastrigin(bounded(seed))
+    }
+}
+
+impl Fitness for Rastrigin {
+    fn calculate_fitness(&mut self, _seed: [u8; 32]) -> Option<f64> {
+        let data = self.0.get_data();
+        let value = 10.0 * data.len() as f64
+            + data
+                .iter()
+                .map(|x| x * x - 10.0 * (2.0 * PI * x).cos())
+                .sum::<f64>();
+        self.0.set_fitness(Some(value));
+        self.0.get_fitness()
+    }
+}
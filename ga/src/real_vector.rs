@@ -0,0 +1,362 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    population::{probability, CrossoverConfig, Genome, MutationConfig},
+    traits::{Crossover, Distance, FitnessRetrieve, FitnessValue, Generate, Mutate},
+};
+
+pub const DEFAULT_DIMENSIONS: usize = 10;
+pub const DEFAULT_BOUNDS: (f64, f64) = (-1.0, 1.0);
+
+/// How genes that leave their bounds are brought back in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum RepairPolicy {
+    #[default]
+    Clamp,
+    /// Mirrors the overshoot back off the violated bound.
+    Reflect,
+    /// Draws a fresh uniform value within the bounds.
+    Resample,
+}
+
+impl RepairPolicy {
+    pub fn repair(&self, value: f64, (lower, upper): (f64, f64), rng: &mut StdRng) -> f64 {
+        if (lower..=upper).contains(&value) {
+            return value;
+        }
+        let width = upper - lower;
+        match self {
+            RepairPolicy::Clamp => value.clamp(lower, upper),
+            RepairPolicy::Reflect if value.is_finite() && width > 0.0 => {
+                let offset = (value - lower).rem_euclid(2.0 * width);
+                if offset > width {
+                    upper - (offset - width)
+                } else {
+                    lower + offset
+                }
+            }
+            RepairPolicy::Reflect => value.clamp(lower, upper),
+            RepairPolicy::Resample => sample(lower, upper, rng),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RealCrossover {
+    /// Simulated binary crossover; larger `eta` keeps offspring closer to the parents.
+    Sbx { eta: f64 },
+    /// Samples each gene from the parents' interval widened by `alpha` on both sides.
+    BlxAlpha { alpha: f64 },
+    /// A random weighted average of the parents.
+    Arithmetic,
+}
+
+impl Default for RealCrossover {
+    fn default() -> Self {
+        RealCrossover::Sbx { eta: 15.0 }
+    }
+}
+
+/// Per-gene mutations, each applied with `gene_mutation_chance`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RealMutation {
+    /// Adds normal noise with standard deviation `sigma` times the gene's bound width.
+    Gaussian { sigma: f64 },
+    /// Polynomial mutation; larger `eta` gives smaller steps.
+    Polynomial { eta: f64 },
+}
+
+impl Default for RealMutation {
+    fn default() -> Self {
+        RealMutation::Gaussian { sigma: 0.1 }
+    }
+}
+
+/// A vector of reals with per-dimension `(lower, upper)` bounds.
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+pub struct RealVector<F = f64> {
+    inner: Genome<Vec<f64>, F>,
+    bounds: Vec<(f64, f64)>,
+    repair: RepairPolicy,
+}
+
+impl<F: FitnessValue> RealVector<F> {
+    pub fn get_data(&self) -> &Vec<f64> {
+        &self.inner.data
+    }
+    pub fn get_bounds(&self) -> &Vec<(f64, f64)> {
+        &self.bounds
+    }
+    /// Number of times a fitness has been set on this genome.
+    pub fn get_evaluations(&self) -> u64 {
+        self.inner.evaluations
+    }
+    pub fn generate_bounded(bounds: Vec<(f64, f64)>, repair: RepairPolicy, seed: [u8; 32]) -> Self {
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let data = bounds
+            .iter()
+            .map(|(l, u)| sample(*l, *u, &mut rng))
+            .collect();
+        RealVector {
+            inner: Genome {
+                data,
+                ..Default::default()
+            },
+            bounds,
+            repair,
+        }
+    }
+    fn with_data(&self, data: Vec<f64>, rng: &mut StdRng) -> Self {
+        let data = data
+            .into_iter()
+            .zip(self.bounds.iter())
+            .map(|(v, b)| self.repair.repair(v, *b, rng))
+            .collect();
+        RealVector {
+            inner: Genome {
+                data,
+                ..Default::default()
+            },
+            bounds: self.bounds.clone(),
+            repair: self.repair,
+        }
+    }
+}
+
+impl<F: FitnessValue> Generate for RealVector<F> {
+    fn generate(seed: [u8; 32]) -> Self {
+        RealVector::generate_bounded(
+            vec![DEFAULT_BOUNDS; DEFAULT_DIMENSIONS],
+            RepairPolicy::default(),
+            seed,
+        )
+    }
+}
+
+impl<F: FitnessValue> Mutate for RealVector<F> {
+    fn mutate(&self, config: &MutationConfig, seed: [u8; 32]) -> Self {
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let data = self
+            .inner
+            .data
+            .iter()
+            .zip(self.bounds.iter())
+            .map(|(&x, &(lower, upper))| {
+                if !rng.gen_bool(probability(config.gene_mutation_chance)) {
+                    return x;
+                }
+                let width = upper - lower;
+                match config.real {
+                    RealMutation::Gaussian { sigma } => {
+                        x + sigma * width * standard_normal(&mut rng)
+                    }
+                    RealMutation::Polynomial { eta } => {
+                        if width <= 0.0 {
+                            return x;
+                        }
+                        let power = 1.0 / (eta + 1.0);
+                        let r: f64 = rng.gen();
+                        let delta = if r < 0.5 {
+                            let xy = 1.0 - (x - lower) / width;
+                            let val = 2.0 * r + (1.0 - 2.0 * r) * xy.powf(eta + 1.0);
+                            val.powf(power) - 1.0
+                        } else {
+                            let xy = 1.0 - (upper - x) / width;
+                            let val = 2.0 * (1.0 - r) + 2.0 * (r - 0.5) * xy.powf(eta + 1.0);
+                            1.0 - val.powf(power)
+                        };
+                        x + delta * width
+                    }
+                }
+            })
+            .collect();
+        self.with_data(data, &mut rng)
+    }
+}
+
+impl<F: FitnessValue> Crossover for RealVector<F> {
+    /// Vectors of different lengths can't be crossed, and the offspring is a
+    /// copy of `self`; see `can_cross`.
+    fn crossover(&self, other: &Self, config: &CrossoverConfig, seed: [u8; 32]) -> Self {
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let first = &self.inner.data;
+        let second = &other.inner.data;
+        if first.len() != second.len() {
+            return self.with_data(first.clone(), &mut rng);
+        }
+
+        let data = match config.real {
+            RealCrossover::Sbx { eta } => first
+                .iter()
+                .zip(second.iter())
+                .map(|(&x1, &x2)| {
+                    let u: f64 = rng.gen();
+                    let beta = if u <= 0.5 {
+                        (2.0 * u).powf(1.0 / (eta + 1.0))
+                    } else {
+                        (1.0 / (2.0 * (1.0 - u))).powf(1.0 / (eta + 1.0))
+                    };
+                    let sign = if rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+                    0.5 * ((x1 + x2) + sign * beta * (x1 - x2))
+                })
+                .collect(),
+            RealCrossover::BlxAlpha { alpha } => first
+                .iter()
+                .zip(second.iter())
+                .map(|(&x1, &x2)| {
+                    let spread = alpha * (x1 - x2).abs();
+                    sample(x1.min(x2) - spread, x1.max(x2) + spread, &mut rng)
+                })
+                .collect(),
+            RealCrossover::Arithmetic => {
+                let weight: f64 = rng.gen();
+                first
+                    .iter()
+                    .zip(second.iter())
+                    .map(|(&x1, &x2)| weight * x1 + (1.0 - weight) * x2)
+                    .collect()
+            }
+        };
+        self.with_data(data, &mut rng)
+    }
+    fn can_cross(&self, other: &Self) -> bool {
+        self.inner.data.len() == other.inner.data.len()
+    }
+}
+
+impl<F: FitnessValue> FitnessRetrieve for RealVector<F> {
+    type Value = F;
+    fn get_fitness(&self) -> Option<F> {
+        self.inner.fitness.clone()
+    }
+    fn set_fitness(&mut self, fitness: Option<F>) {
+        self.inner.set_fitness(fitness);
+    }
+}
+
+/// Euclidean distance over the dimensions both vectors have.
+impl<F> Distance for RealVector<F> {
+    fn distance(&self, other: &Self) -> f64 {
+        let (a, b) = (&self.inner.data, &other.inner.data);
+        a.iter()
+            .zip(b)
+            .map(|(x, y)| (x - y).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+}
+
+/// Box-Muller transform.
+fn standard_normal(rng: &mut StdRng) -> f64 {
+    let u1: f64 = 1.0 - rng.gen::<f64>();
+    let u2: f64 = rng.gen();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+fn sample(lower: f64, upper: f64, rng: &mut StdRng) -> f64 {
+    if lower < upper {
+        rng.gen_range(lower..=upper)
+    } else {
+        lower
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{RealCrossover, RealMutation, RealVector, RepairPolicy};
+    use crate::{
+        population::{CrossoverConfig, MutationConfig},
+        traits::{Crossover, FitnessRetrieve, Mutate},
+    };
+
+    #[test]
+    fn test_repair() {
+        let mut rng: StdRng = SeedableRng::from_seed([0; 32]);
+        assert_eq!(1.0, RepairPolicy::Clamp.repair(1.5, (0.0, 1.0), &mut rng));
+        assert_eq!(0.5, RepairPolicy::Reflect.repair(1.5, (0.0, 1.0), &mut rng));
+        assert_eq!(
+            0.25,
+            RepairPolicy::Reflect.repair(-0.25, (0.0, 1.0), &mut rng)
+        );
+        assert_eq!(
+            0.75,
+            RepairPolicy::Reflect.repair(2.75, (0.0, 1.0), &mut rng)
+        );
+        let resampled = RepairPolicy::Resample.repair(-3.0, (0.0, 1.0), &mut rng);
+        assert!((0.0..=1.0).contains(&resampled));
+    }
+
+    #[test]
+    fn test_operators_respect_bounds() {
+        let bounds = vec![(-1.0, 1.0), (0.0, 10.0), (5.0, 5.5)];
+        let crossovers = [
+            RealCrossover::Sbx { eta: 2.0 },
+            RealCrossover::BlxAlpha { alpha: 0.5 },
+            RealCrossover::Arithmetic,
+        ];
+        let mutations = [
+            RealMutation::Gaussian { sigma: 2.0 },
+            RealMutation::Polynomial { eta: 5.0 },
+        ];
+        for repair in [
+            RepairPolicy::Clamp,
+            RepairPolicy::Reflect,
+            RepairPolicy::Resample,
+        ] {
+            for seed in 0..10u8 {
+                let first: RealVector =
+                    RealVector::generate_bounded(bounds.clone(), repair, [seed; 32]);
+                let second = RealVector::generate_bounded(bounds.clone(), repair, [seed + 50; 32]);
+                let mut offspring = Vec::new();
+                for real in crossovers.iter().cloned() {
+                    let config = CrossoverConfig {
+                        real,
+                        ..Default::default()
+                    };
+                    offspring.push(first.crossover(&second, &config, [seed; 32]));
+                }
+                for real in mutations.iter().cloned() {
+                    let config = MutationConfig {
+                        gene_mutation_chance: 1.0,
+                        real,
+                        ..Default::default()
+                    };
+                    offspring.push(first.mutate(&config, [seed; 32]));
+                }
+                for o in offspring {
+                    assert_eq!(3, o.get_data().len());
+                    assert!(o
+                        .get_data()
+                        .iter()
+                        .zip(bounds.iter())
+                        .all(|(v, (l, u))| (*l..=*u).contains(v)));
+                }
+            }
+        }
+
+        let first =
+            RealVector::<f64>::generate_bounded(bounds.clone(), RepairPolicy::Clamp, [1; 32]);
+        let shorter =
+            RealVector::generate_bounded(bounds[..2].to_vec(), RepairPolicy::Clamp, [2; 32]);
+        assert!(first.can_cross(&first));
+        assert!(!first.can_cross(&shorter));
+        let offspring = first.crossover(&shorter, &CrossoverConfig::default(), [3; 32]);
+        assert_eq!(first.get_data(), offspring.get_data());
+    }
+
+    #[test]
+    fn test_serialize_keeps_fitness() {
+        let mut v =
+            RealVector::generate_bounded(vec![(0.0, 1.0); 3], RepairPolicy::Reflect, [4; 32]);
+        v.set_fitness(Some(0.5));
+        let json = serde_json::to_string(&v).unwrap();
+        let loaded: RealVector = serde_json::from_str(&json).unwrap();
+        assert_eq!(v.get_data(), loaded.get_data());
+        assert_eq!(v.get_bounds(), loaded.get_bounds());
+        assert_eq!(Some(0.5), loaded.get_fitness());
+        assert_eq!(1, loaded.get_evaluations());
+    }
+}
@@ -0,0 +1,129 @@
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+/// How `Population::tick` brings offspring into the population.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Replacement {
+    /// Every tick breeds a whole new population after the elites.
+    #[default]
+    Generational,
+    /// Every tick breeds `offspring` members and puts each in place of a
+    /// current member chosen by `policy`. The best `elitism_count` members are
+    /// never replaced. `mutate_count` and `crossover_count` set the odds of
+    /// each operator; with both at zero, new members are generated.
+    SteadyState {
+        offspring: usize,
+        policy: ReplacementPolicy,
+    },
+    /// Every tick breeds `mutate_count + crossover_count` offspring, choosing
+    /// operators as `SteadyState` does. Each replaces its most similar parent
+    /// unless that parent is better. A parent is replaced at most once a tick;
+    /// later siblings compete with their remaining parents.
+    DeterministicCrowding,
+    /// Like `DeterministicCrowding`, but the offspring wins with probability
+    /// `f / (f + g)`, where `f` and `g` are the offspring's and parent's
+    /// fitness rescaled as in `niching::goodness`. Elites are only replaced by
+    /// offspring at least as good.
+    ProbabilisticCrowding,
+    /// Like `DeterministicCrowding`, but each offspring competes with the most
+    /// similar of `window` members drawn at random.
+    RestrictedTournament { window: usize },
+}
+
+impl Replacement {
+    /// True for the crowding strategies, which need a distance; see
+    /// `Population::set_distance`.
+    pub fn is_crowding(&self) -> bool {
+        matches!(
+            self,
+            Replacement::DeterministicCrowding
+                | Replacement::ProbabilisticCrowding
+                | Replacement::RestrictedTournament { .. }
+        )
+    }
+}
+
+/// The member an offspring replaces in steady-state mode.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ReplacementPolicy {
+    #[default]
+    Worst,
+    Random,
+    /// The member created the longest ago, the worst of them on ties.
+    Oldest,
+    /// The worst of `size` members drawn at random.
+    TournamentLoser {
+        size: usize,
+    },
+    /// The worse of the offspring's parents, or the worst member if both have
+    /// already been replaced this tick.
+    Parent,
+}
+
+impl ReplacementPolicy {
+    /// False for tournaments of 0.
+    pub fn is_valid(&self) -> bool {
+        !matches!(self, ReplacementPolicy::TournamentLoser { size: 0 })
+    }
+
+    /// Picks one of the `available` positions. Members are sorted best first,
+    /// `born` holds the generation each was created in and `parents` the
+    /// offspring's parents.
+    pub fn choose(
+        &self,
+        available: &[bool],
+        born: &[i64],
+        parents: &[usize],
+        rng: &mut StdRng,
+    ) -> Option<usize> {
+        let candidates: Vec<usize> = (0..available.len()).filter(|i| available[*i]).collect();
+        match self {
+            ReplacementPolicy::Worst => candidates.last().copied(),
+            ReplacementPolicy::Random => candidates.choose(rng).copied(),
+            ReplacementPolicy::Oldest => candidates.iter().rev().copied().min_by_key(|i| born[*i]),
+            ReplacementPolicy::TournamentLoser { size } => {
+                if candidates.is_empty() {
+                    return None;
+                }
+                (0..(*size).max(1))
+                    .map(|_| candidates[rng.gen_range(0..candidates.len())])
+                    .max()
+            }
+            ReplacementPolicy::Parent => parents
+                .iter()
+                .copied()
+                .filter(|p| available.get(*p) == Some(&true))
+                .max()
+                .or(candidates.last().copied()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::ReplacementPolicy;
+
+    #[test]
+    fn test_choose() {
+        let mut rng: StdRng = SeedableRng::from_seed([0; 32]);
+        let available = [false, true, true, true, false];
+        let born = [0, 3, 1, 1, 0];
+        let mut choose = |policy: ReplacementPolicy, parents: &[usize]| {
+            policy.choose(&available, &born, parents, &mut rng)
+        };
+        assert_eq!(Some(3), choose(ReplacementPolicy::Worst, &[]));
+        assert_eq!(Some(3), choose(ReplacementPolicy::Oldest, &[]));
+        assert_eq!(Some(2), choose(ReplacementPolicy::Parent, &[0, 2]));
+        assert_eq!(Some(3), choose(ReplacementPolicy::Parent, &[0, 4]));
+        assert!((1..4).contains(&choose(ReplacementPolicy::Random, &[]).unwrap()));
+        assert!(
+            (1..4).contains(&choose(ReplacementPolicy::TournamentLoser { size: 2 }, &[]).unwrap())
+        );
+        assert_eq!(
+            None,
+            ReplacementPolicy::Worst.choose(&[false], &[0], &[], &mut rng)
+        );
+    }
+}
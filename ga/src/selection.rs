@@ -0,0 +1,361 @@
+use rand::{rngs::StdRng, seq::index, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{objective::Objective, traits::FitnessValue};
+
+/// The members an island of an `Archipelago` sends to other islands.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum EmigrantSelection {
+    /// The best members, which stay on their island as well.
+    #[default]
+    Best,
+    /// Members picked by a selection method. `Uniform` picks at random.
+    Select(SelectionMethod),
+}
+
+/// Picks parents for the next generation.
+///
+/// `fitnesses` holds one entry per population member; the returned values are
+/// indices into it. Members without a fitness, or with an invalid fitness such
+/// as NaN, are treated as the worst.
+pub trait Selection {
+    fn select<F: FitnessValue>(
+        &self,
+        fitnesses: &[Option<F>],
+        objective: Objective,
+        count: usize,
+        seed: [u8; 32],
+    ) -> Vec<usize>;
+}
+
+/// Serializable choice of selection strategy, stored in `PopulationConfig`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SelectionMethod {
+    #[default]
+    Uniform,
+    Tournament {
+        size: usize,
+    },
+    Roulette,
+    Rank,
+    StochasticUniversal,
+    Truncation {
+        proportion: f64,
+    },
+}
+
+impl SelectionMethod {
+    /// False for tournaments of 0 and truncation proportions outside `(0, 1]`.
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            SelectionMethod::Tournament { size } => size > 0,
+            SelectionMethod::Truncation { proportion } => proportion > 0.0 && proportion <= 1.0,
+            _ => true,
+        }
+    }
+}
+
+impl Selection for SelectionMethod {
+    fn select<F: FitnessValue>(
+        &self,
+        fitnesses: &[Option<F>],
+        objective: Objective,
+        count: usize,
+        seed: [u8; 32],
+    ) -> Vec<usize> {
+        match self {
+            SelectionMethod::Uniform => Uniform.select(fitnesses, objective, count, seed),
+            SelectionMethod::Tournament { size } => {
+                Tournament { size: *size }.select(fitnesses, objective, count, seed)
+            }
+            SelectionMethod::Roulette => Roulette.select(fitnesses, objective, count, seed),
+            SelectionMethod::Rank => Rank.select(fitnesses, objective, count, seed),
+            SelectionMethod::StochasticUniversal => {
+                StochasticUniversal.select(fitnesses, objective, count, seed)
+            }
+            SelectionMethod::Truncation { proportion } => Truncation {
+                proportion: *proportion,
+            }
+            .select(fitnesses, objective, count, seed),
+        }
+    }
+}
+
+/// Ignores fitness and picks distinct members uniformly at random.
+#[derive(Debug, Default, Clone)]
+pub struct Uniform;
+
+impl Selection for Uniform {
+    fn select<F: FitnessValue>(
+        &self,
+        fitnesses: &[Option<F>],
+        _objective: Objective,
+        count: usize,
+        seed: [u8; 32],
+    ) -> Vec<usize> {
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        if fitnesses.is_empty() {
+            return Vec::new();
+        }
+        if count <= fitnesses.len() {
+            index::sample(&mut rng, fitnesses.len(), count).into_vec()
+        } else {
+            (0..count)
+                .map(|_| rng.gen_range(0..fitnesses.len()))
+                .collect()
+        }
+    }
+}
+
+/// Runs a tournament of `size` random members for each parent; the fittest wins.
+#[derive(Debug, Clone)]
+pub struct Tournament {
+    pub size: usize,
+}
+
+impl Selection for Tournament {
+    fn select<F: FitnessValue>(
+        &self,
+        fitnesses: &[Option<F>],
+        objective: Objective,
+        count: usize,
+        seed: [u8; 32],
+    ) -> Vec<usize> {
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        if fitnesses.is_empty() {
+            return Vec::new();
+        }
+        (0..count)
+            .map(|_| {
+                (0..self.size.max(1))
+                    .map(|_| rng.gen_range(0..fitnesses.len()))
+                    .reduce(|best, i| {
+                        if objective.is_better(&fitnesses[i], &fitnesses[best]) {
+                            i
+                        } else {
+                            best
+                        }
+                    })
+                    .unwrap()
+            })
+            .collect()
+    }
+}
+
+/// Fitness-proportional selection. Fitnesses are shifted so the worst member has weight zero.
+#[derive(Debug, Default, Clone)]
+pub struct Roulette;
+
+impl Selection for Roulette {
+    fn select<F: FitnessValue>(
+        &self,
+        fitnesses: &[Option<F>],
+        objective: Objective,
+        count: usize,
+        seed: [u8; 32],
+    ) -> Vec<usize> {
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let weights = proportional_weights(fitnesses, objective);
+        (0..count)
+            .filter_map(|_| spin(&weights, rng.gen()))
+            .collect()
+    }
+}
+
+/// Linear ranking: the best of `n` members has weight `n`, the worst has weight 1.
+#[derive(Debug, Default, Clone)]
+pub struct Rank;
+
+impl Selection for Rank {
+    fn select<F: FitnessValue>(
+        &self,
+        fitnesses: &[Option<F>],
+        objective: Objective,
+        count: usize,
+        seed: [u8; 32],
+    ) -> Vec<usize> {
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let mut weights = vec![0.0; fitnesses.len()];
+        ranked(fitnesses, objective)
+            .into_iter()
+            .enumerate()
+            .for_each(|(rank, i)| weights[i] = (fitnesses.len() - rank) as f64);
+        (0..count)
+            .filter_map(|_| spin(&weights, rng.gen()))
+            .collect()
+    }
+}
+
+/// Fitness-proportional selection with evenly spaced pointers, which keeps the
+/// spread of picks close to the expected counts.
+#[derive(Debug, Default, Clone)]
+pub struct StochasticUniversal;
+
+impl Selection for StochasticUniversal {
+    fn select<F: FitnessValue>(
+        &self,
+        fitnesses: &[Option<F>],
+        objective: Objective,
+        count: usize,
+        seed: [u8; 32],
+    ) -> Vec<usize> {
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        if fitnesses.is_empty() || count == 0 {
+            return Vec::new();
+        }
+        let weights = proportional_weights(fitnesses, objective);
+        let total: f64 = weights.iter().sum();
+        let step = total / count as f64;
+        let start = rng.gen::<f64>() * step;
+
+        let mut selected = Vec::with_capacity(count);
+        let mut cumulative = 0.0;
+        let mut pointers = (0..count).map(|i| start + step * i as f64).peekable();
+        for (i, w) in weights.iter().enumerate() {
+            cumulative += w;
+            while pointers.next_if(|p| *p < cumulative).is_some() {
+                selected.push(i);
+            }
+        }
+        // Floating point error can leave the last pointers unassigned.
+        selected.extend(pointers.map(|_| fitnesses.len() - 1));
+        selected
+    }
+}
+
+/// Picks uniformly among the best `proportion` of the population.
+#[derive(Debug, Clone)]
+pub struct Truncation {
+    pub proportion: f64,
+}
+
+impl Selection for Truncation {
+    fn select<F: FitnessValue>(
+        &self,
+        fitnesses: &[Option<F>],
+        objective: Objective,
+        count: usize,
+        seed: [u8; 32],
+    ) -> Vec<usize> {
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+        let order = ranked(fitnesses, objective);
+        if order.is_empty() {
+            return Vec::new();
+        }
+        let keep = ((order.len() as f64 * self.proportion).ceil() as usize).clamp(1, order.len());
+        (0..count).map(|_| order[rng.gen_range(0..keep)]).collect()
+    }
+}
+
+/// Member indices ordered from best to worst.
+fn ranked<F: FitnessValue>(fitnesses: &[Option<F>], objective: Objective) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..fitnesses.len()).collect();
+    order.sort_by(|&a, &b| objective.compare(&fitnesses[a], &fitnesses[b]));
+    order
+}
+
+/// Weights from `Objective::oriented` fitness, shifted so the worst member has weight zero.
+fn proportional_weights<F: FitnessValue>(
+    fitnesses: &[Option<F>],
+    objective: Objective,
+) -> Vec<f64> {
+    let fitnesses: Vec<Option<f64>> = fitnesses.iter().map(|f| objective.oriented(f)).collect();
+    let min = fitnesses
+        .iter()
+        .flatten()
+        .filter(|f| f.is_finite())
+        .fold(f64::INFINITY, |acc, f| acc.min(*f));
+    let weights: Vec<f64> = fitnesses
+        .iter()
+        .map(|f| match f {
+            Some(f) if f.is_finite() => f - min,
+            _ => 0.0,
+        })
+        .collect();
+    if weights.iter().sum::<f64>() > 0.0 {
+        weights
+    } else {
+        // Everyone is equally fit, so fall back to picking uniformly.
+        vec![1.0; fitnesses.len()]
+    }
+}
+
+/// Picks an index with probability proportional to its weight, given `r` in `[0, 1)`.
+fn spin(weights: &[f64], r: f64) -> Option<usize> {
+    let total: f64 = weights.iter().sum();
+    let target = r * total;
+    let mut cumulative = 0.0;
+    for (i, w) in weights.iter().enumerate() {
+        cumulative += w;
+        if target < cumulative {
+            return Some(i);
+        }
+    }
+    weights.iter().rposition(|w| *w > 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Selection, SelectionMethod};
+    use crate::objective::Objective;
+
+    const FITNESSES: [Option<f64>; 6] =
+        [Some(1.0), None, Some(10.0), Some(3.0), Some(1.0), Some(7.0)];
+
+    #[test]
+    fn test_selects_requested_count() {
+        let methods = [
+            SelectionMethod::Uniform,
+            SelectionMethod::Tournament { size: 3 },
+            SelectionMethod::Roulette,
+            SelectionMethod::Rank,
+            SelectionMethod::StochasticUniversal,
+            SelectionMethod::Truncation { proportion: 0.5 },
+        ];
+        for method in methods {
+            let selected = method.select(&FITNESSES, Objective::Maximize, 4, [3; 32]);
+            assert_eq!(4, selected.len(), "{method:?}");
+            assert!(selected.iter().all(|i| *i < FITNESSES.len()), "{method:?}");
+        }
+    }
+
+    #[test]
+    fn test_pressure() {
+        // The worst valid member and the unevaluated one can never be picked proportionally.
+        let selected =
+            SelectionMethod::Roulette.select(&FITNESSES, Objective::Maximize, 100, [5; 32]);
+        assert!(selected.iter().all(|i| *i != 0 && *i != 1 && *i != 4));
+
+        let selected = SelectionMethod::StochasticUniversal.select(
+            &FITNESSES,
+            Objective::Maximize,
+            17,
+            [5; 32],
+        );
+        assert_eq!(9, selected.iter().filter(|i| **i == 2).count());
+
+        let selected = SelectionMethod::Truncation { proportion: 0.3 }.select(
+            &FITNESSES,
+            Objective::Maximize,
+            20,
+            [5; 32],
+        );
+        assert!(selected.iter().all(|i| *i == 2 || *i == 5));
+
+        let selected = SelectionMethod::Truncation { proportion: 0.3 }.select(
+            &FITNESSES,
+            Objective::Minimize,
+            20,
+            [5; 32],
+        );
+        assert!(selected.iter().all(|i| *i == 0 || *i == 4));
+
+        let selected = SelectionMethod::Tournament { size: 6 }.select(
+            &FITNESSES,
+            Objective::Maximize,
+            20,
+            [5; 32],
+        );
+        assert!(selected.contains(&2));
+    }
+}
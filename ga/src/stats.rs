@@ -0,0 +1,209 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{diversity::Diversity, objective::Objective, traits::FitnessValue};
+
+/// Summary of the population at the end of a generation.
+///
+/// Fitness figures are taken from `FitnessValue::to_f64`, which for tuple
+/// fitnesses is the first component, with `best` and `worst` picked by the fitness type's own ordering. They ignore members
+/// without a fitness and invalid or non-finite values, and are `None` when no
+/// member has a usable fitness. These are summaries for reports; stop
+/// conditions compare the fitness values themselves (see `RunProgress`).
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GenerationStats {
+    pub generation: i64,
+    pub best: Option<f64>,
+    pub worst: Option<f64>,
+    pub mean: Option<f64>,
+    pub median: Option<f64>,
+    pub std_dev: Option<f64>,
+    /// Confidence interval of the best member's mean fitness, when noise
+    /// handling is enabled and the member has at least two samples.
+    #[serde(default)]
+    pub best_interval: Option<(f64, f64)>,
+    /// Fitness evaluations made during this generation.
+    pub evaluations: u64,
+    /// Fitness evaluations made by the population so far.
+    pub total_evaluations: u64,
+    /// Members whose fitness is `None`.
+    pub none_count: usize,
+    /// New members that took their fitness from the cache instead of being evaluated.
+    #[serde(default)]
+    pub cache_hits: u64,
+    /// `cache_hits` as a share of all new members that needed a fitness. `None`
+    /// without a cache.
+    #[serde(default)]
+    pub cache_hit_rate: Option<f64>,
+    /// `None` unless diversity is measured; see `Population::enable_diversity`.
+    #[serde(default)]
+    pub diversity: Option<Diversity>,
+    pub duration: Duration,
+}
+
+impl GenerationStats {
+    /// `best` and `worst` follow `objective`.
+    pub fn new<F: FitnessValue>(
+        generation: i64,
+        fitnesses: &[Option<F>],
+        objective: Objective,
+    ) -> Self {
+        let mut usable: Vec<&F> = fitnesses
+            .iter()
+            .flatten()
+            .filter(|f| f.is_valid() && f.to_f64().is_finite())
+            .collect();
+        usable.sort_by(|a, b| a.compare(b));
+        let mut valid: Vec<f64> = usable.iter().map(|f| f.to_f64()).collect();
+        valid.sort_by(|a, b| a.total_cmp(b));
+
+        let mut stats = GenerationStats {
+            generation,
+            none_count: fitnesses.iter().filter(|f| f.is_none()).count(),
+            ..Default::default()
+        };
+        if valid.is_empty() {
+            return stats;
+        }
+
+        let count = valid.len() as f64;
+        let mean = valid.iter().sum::<f64>() / count;
+        let variance = valid.iter().map(|f| (f - mean).powi(2)).sum::<f64>() / count;
+        let middle = valid.len() / 2;
+        let (lowest, highest) = (
+            usable.first().map(|f| f.to_f64()),
+            usable.last().map(|f| f.to_f64()),
+        );
+        (stats.best, stats.worst) = match objective {
+            Objective::Maximize => (highest, lowest),
+            Objective::Minimize => (lowest, highest),
+        };
+        stats.mean = Some(mean);
+        stats.median = Some(if valid.len().is_multiple_of(2) {
+            (valid[middle - 1] + valid[middle]) / 2.0
+        } else {
+            valid[middle]
+        });
+        stats.std_dev = Some(variance.sqrt());
+        stats
+    }
+}
+
+/// Statistics for every generation of a run, in order.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct History {
+    pub generations: Vec<GenerationStats>,
+}
+
+impl History {
+    pub fn push(&mut self, stats: GenerationStats) {
+        self.generations.push(stats);
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// One row per generation with a header line. Missing values are left empty
+    /// and durations are in seconds.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "generation,best,worst,mean,median,std_dev,best_lower,best_upper,evaluations,total_evaluations,none_count,cache_hits,cache_hit_rate,mean_distance,unique,gene_entropy,duration\n",
+        );
+        let cell = |v: Option<f64>| v.map(|v| v.to_string()).unwrap_or_default();
+        for s in &self.generations {
+            let diversity = s.diversity.clone().unwrap_or_default();
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+                s.generation,
+                cell(s.best),
+                cell(s.worst),
+                cell(s.mean),
+                cell(s.median),
+                cell(s.std_dev),
+                cell(s.best_interval.map(|i| i.0)),
+                cell(s.best_interval.map(|i| i.1)),
+                s.evaluations,
+                s.total_evaluations,
+                s.none_count,
+                s.cache_hits,
+                cell(s.cache_hit_rate),
+                cell(diversity.mean_distance),
+                diversity.unique.map(|u| u.to_string()).unwrap_or_default(),
+                cell(diversity.gene_entropy),
+                s.duration.as_secs_f64()
+            ));
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{GenerationStats, History};
+    use crate::{diversity::Diversity, objective::Objective};
+
+    #[test]
+    fn test_stats() {
+        let stats = GenerationStats::new(
+            4,
+            &[
+                Some(1.0),
+                None,
+                Some(4.0),
+                Some(f64::NAN),
+                Some(3.0),
+                Some(8.0),
+            ],
+            Objective::Maximize,
+        );
+        assert_eq!(Some(8.0), stats.best);
+        assert_eq!(Some(1.0), stats.worst);
+        assert_eq!(Some(4.0), stats.mean);
+        assert_eq!(Some(3.5), stats.median);
+        assert_eq!(Some(2.5495097567963922), stats.std_dev);
+        assert_eq!(1, stats.none_count);
+
+        let stats = GenerationStats::new(4, &[Some(1.0), Some(8.0)], Objective::Minimize);
+        assert_eq!((Some(1.0), Some(8.0)), (stats.best, stats.worst));
+
+        let empty = GenerationStats::new(1, &[None::<f64>], Objective::Maximize);
+        assert_eq!(None, empty.best);
+        assert_eq!(None, empty.std_dev);
+    }
+
+    #[test]
+    fn test_export() {
+        let mut history = History::default();
+        history.push(GenerationStats {
+            duration: Duration::from_millis(1500),
+            evaluations: 10,
+            total_evaluations: 10,
+            ..GenerationStats::new(1, &[Some(2.0), None], Objective::Maximize)
+        });
+        history.push(GenerationStats {
+            duration: Duration::from_millis(250),
+            evaluations: 4,
+            total_evaluations: 14,
+            diversity: Some(Diversity {
+                mean_distance: Some(0.5),
+                unique: Some(2),
+                gene_entropy: None,
+            }),
+            ..GenerationStats::new(2, &[Some(1.0), Some(3.0)], Objective::Maximize)
+        });
+        assert_eq!(
+            "generation,best,worst,mean,median,std_dev,best_lower,best_upper,evaluations,total_evaluations,none_count,cache_hits,cache_hit_rate,mean_distance,unique,gene_entropy,duration\n1,2,2,2,2,0,,,10,10,1,0,,,,,1.5\n2,3,1,2,2,1,,,4,14,0,0,,0.5,2,,0.25\n",
+            history.to_csv()
+        );
+        let json = history.to_json().unwrap();
+        assert_eq!(history, History::from_json(&json).unwrap());
+    }
+}
@@ -0,0 +1,345 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    diversity::{Diversity, DiversityMetric},
+    objective::Objective,
+    stats::GenerationStats,
+    traits::FitnessValue,
+};
+
+/// Stop conditions for `Population::run`, composable with `Any` and `All`.
+/// `F` is the fitness type that `TargetFitness` is given in.
+///
+/// ```
+/// use std::time::Duration;
+/// use ga::termination::Termination;
+///
+/// let stop = Termination::MaxGenerations(1000)
+///     .or(Termination::TargetFitness(0.0))
+///     .or(Termination::Stagnation(50).and(Termination::TimeLimit(Duration::from_secs(10))));
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Termination<F = f64> {
+    /// The population has reached this many generations in total.
+    MaxGenerations(i64),
+    /// This many fitness evaluations have been made in total.
+    MaxEvaluations(u64),
+    /// The best fitness is at least this good according to the population's
+    /// `Objective`, compared with `FitnessValue::compare`.
+    TargetFitness(F),
+    /// Wall-clock time spent in this run.
+    TimeLimit(Duration),
+    /// The best fitness has not improved for this many generations.
+    Stagnation(usize),
+    /// The standard deviation of the population's fitness, by
+    /// `FitnessValue::to_f64`, has fallen below `threshold`. Needs no
+    /// `Diversity` measures.
+    DiversityCollapse {
+        threshold: f64,
+    },
+    /// A `Diversity` measure has fallen below `threshold`. `Population::run`
+    /// starts measuring diversity when a condition needs it, and refuses
+    /// measures the population can't take.
+    LowDiversity {
+        metric: DiversityMetric,
+        threshold: f64,
+    },
+    Any(Vec<Termination<F>>),
+    All(Vec<Termination<F>>),
+    /// An observer returned `Control::Stop`. Only reported by `Population::run`;
+    /// it is never met when checked.
+    ObserverStop,
+}
+
+impl<F> Termination<F> {
+    pub fn or(self, other: Termination<F>) -> Termination<F> {
+        match self {
+            Termination::Any(mut conditions) => {
+                conditions.push(other);
+                Termination::Any(conditions)
+            }
+            _ => Termination::Any(vec![self, other]),
+        }
+    }
+
+    pub fn and(self, other: Termination<F>) -> Termination<F> {
+        match self {
+            Termination::All(mut conditions) => {
+                conditions.push(other);
+                Termination::All(conditions)
+            }
+            _ => Termination::All(vec![self, other]),
+        }
+    }
+
+    /// True if this or any nested condition checks `Diversity`.
+    pub fn uses_diversity(&self) -> bool {
+        !self.diversity_metrics().is_empty()
+    }
+
+    /// The `Diversity` measures this and any nested condition check.
+    pub fn diversity_metrics(&self) -> Vec<DiversityMetric> {
+        match self {
+            Termination::LowDiversity { metric, .. } => vec![*metric],
+            Termination::Any(conditions) | Termination::All(conditions) => conditions
+                .iter()
+                .flat_map(|c| c.diversity_metrics())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl<F: FitnessValue> Termination<F> {
+    /// The condition that is met, if any. For `Any` this is the first member
+    /// that is met; `All` is returned whole.
+    pub fn check(&self, progress: &RunProgress<F>) -> Option<Termination<F>> {
+        let met = match self {
+            Termination::MaxGenerations(n) => progress.generations >= *n,
+            Termination::MaxEvaluations(n) => progress.evaluations >= *n,
+            Termination::TargetFitness(target) => {
+                progress.best_fitness.is_some()
+                    && !progress
+                        .objective
+                        .is_better(&Some(target.clone()), &progress.best_fitness)
+            }
+            Termination::TimeLimit(limit) => progress.started.elapsed() >= *limit,
+            Termination::Stagnation(n) => progress.stagnant_generations >= *n,
+            Termination::DiversityCollapse { threshold } => {
+                progress.fitness_spread.is_some_and(|s| s < *threshold)
+            }
+            Termination::LowDiversity { metric, threshold } => progress
+                .diversity
+                .as_ref()
+                .and_then(|d| d.get(*metric))
+                .is_some_and(|v| v < *threshold),
+            Termination::Any(conditions) => {
+                return conditions.iter().find_map(|c| c.check(progress));
+            }
+            Termination::All(conditions) => {
+                !conditions.is_empty() && conditions.iter().all(|c| c.check(progress).is_some())
+            }
+            Termination::ObserverStop => false,
+        };
+        met.then(|| self.clone())
+    }
+}
+
+impl Termination<f64> {
+    /// The same conditions for fitness type `F`, with targets built by
+    /// `FitnessValue::from_f64`. `None` if `F` can't be built from a target.
+    pub fn to_fitness<F: FitnessValue>(&self) -> Option<Termination<F>> {
+        Some(match self {
+            Termination::MaxGenerations(n) => Termination::MaxGenerations(*n),
+            Termination::MaxEvaluations(n) => Termination::MaxEvaluations(*n),
+            Termination::TargetFitness(target) => Termination::TargetFitness(F::from_f64(*target)?),
+            Termination::TimeLimit(limit) => Termination::TimeLimit(*limit),
+            Termination::Stagnation(n) => Termination::Stagnation(*n),
+            Termination::DiversityCollapse { threshold } => Termination::DiversityCollapse {
+                threshold: *threshold,
+            },
+            Termination::LowDiversity { metric, threshold } => Termination::LowDiversity {
+                metric: *metric,
+                threshold: *threshold,
+            },
+            Termination::Any(conditions) => Termination::Any(
+                conditions
+                    .iter()
+                    .map(|c| c.to_fitness())
+                    .collect::<Option<_>>()?,
+            ),
+            Termination::All(conditions) => Termination::All(
+                conditions
+                    .iter()
+                    .map(|c| c.to_fitness())
+                    .collect::<Option<_>>()?,
+            ),
+            Termination::ObserverStop => Termination::ObserverStop,
+        })
+    }
+}
+
+/// What a run has done so far, as seen by stop conditions.
+#[derive(Debug, Clone)]
+pub struct RunProgress<F = f64> {
+    pub started: Instant,
+    /// Generations completed by the population in total.
+    pub generations: i64,
+    /// Fitness evaluations made by the population in total.
+    pub evaluations: u64,
+    /// The best fitness seen so far, in the fitness type's own order rather
+    /// than as the `f64` summary in `GenerationStats`.
+    pub best_fitness: Option<F>,
+    pub stagnant_generations: usize,
+    pub fitness_spread: Option<f64>,
+    pub diversity: Option<Diversity>,
+    pub objective: Objective,
+}
+
+impl<F: FitnessValue> RunProgress<F> {
+    /// Starts tracking from the population's current state, where `best` is
+    /// its best member's fitness.
+    pub fn new(stats: &GenerationStats, best: Option<F>, objective: Objective) -> Self {
+        RunProgress {
+            started: Instant::now(),
+            generations: stats.generation,
+            evaluations: stats.total_evaluations,
+            best_fitness: best,
+            stagnant_generations: 0,
+            fitness_spread: stats.std_dev,
+            diversity: stats.diversity.clone(),
+            objective,
+        }
+    }
+
+    /// Records a finished generation.
+    pub fn update(&mut self, stats: &GenerationStats, best: Option<F>) {
+        self.generations = stats.generation;
+        self.evaluations = stats.total_evaluations;
+
+        if self.objective.is_better(&best, &self.best_fitness) {
+            self.best_fitness = best;
+            self.stagnant_generations = 0;
+        } else {
+            self.stagnant_generations += 1;
+        }
+
+        self.fitness_spread = stats.std_dev;
+        self.diversity = stats.diversity.clone();
+    }
+}
+
+/// Regenerates most of the population whenever `trigger` is met at the start
+/// of `Population::tick`. Progress such as stagnation is counted from the first
+/// tick and again from each restart, and is not part of a checkpoint;
+/// generation and evaluation limits still count in total. Targets are
+/// converted to the population's fitness type with `FitnessValue::from_f64`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RestartConfig {
+    pub trigger: Termination,
+    /// The best members kept through a restart.
+    #[serde(default)]
+    pub keep: usize,
+}
+
+/// The outcome of `Population::run`.
+#[derive(Debug, Clone)]
+pub struct RunReport<T, F = f64> {
+    /// The condition that ended the run.
+    pub reason: Termination<F>,
+    pub best: T,
+    /// The population's generation count when the run ended.
+    pub generations: i64,
+    pub evaluations: u64,
+    pub elapsed: Duration,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RunProgress, Termination};
+    use crate::{
+        diversity::{Diversity, DiversityMetric},
+        objective::Objective,
+        stats::GenerationStats,
+    };
+
+    fn stats(generation: i64, evaluations: u64, fitnesses: &[Option<f64>]) -> GenerationStats {
+        GenerationStats {
+            total_evaluations: evaluations,
+            ..GenerationStats::new(generation, fitnesses, Objective::Maximize)
+        }
+    }
+
+    fn record(
+        progress: &mut RunProgress,
+        generation: i64,
+        evaluations: u64,
+        fitnesses: &[Option<f64>],
+    ) {
+        let best = fitnesses
+            .iter()
+            .fold(None, |b, f| progress.objective.best(b, *f));
+        progress.update(&stats(generation, evaluations, fitnesses), best);
+    }
+
+    #[test]
+    fn test_composition() {
+        let mut progress = RunProgress::new(&stats(0, 0, &[]), None, Objective::Maximize);
+        let stop = Termination::MaxGenerations(3)
+            .or(Termination::Stagnation(2).and(Termination::TargetFitness(5.0)));
+        assert_eq!(None, stop.check(&progress));
+
+        record(&mut progress, 1, 10, &[Some(6.0), Some(2.0)]);
+        record(&mut progress, 2, 20, &[Some(6.0), None]);
+        assert_eq!(None, stop.check(&progress));
+        record(&mut progress, 2, 30, &[Some(6.0), Some(6.0)]);
+        assert_eq!(2, progress.stagnant_generations);
+        assert_eq!(Some(0.0), progress.fitness_spread);
+        assert_eq!(
+            Some(Termination::Stagnation(2).and(Termination::TargetFitness(5.0))),
+            stop.check(&progress)
+        );
+
+        record(&mut progress, 3, 40, &[Some(7.0)]);
+        assert_eq!(0, progress.stagnant_generations);
+        assert_eq!(Some(Termination::MaxGenerations(3)), stop.check(&progress));
+        assert_eq!(
+            Some(Termination::DiversityCollapse { threshold: 0.1 }),
+            Termination::DiversityCollapse { threshold: 0.1 }.check(&progress)
+        );
+
+        let low = Termination::LowDiversity {
+            metric: DiversityMetric::Unique,
+            threshold: 2.0,
+        };
+        assert_eq!(
+            vec![DiversityMetric::Unique],
+            Termination::MaxGenerations(5)
+                .or(low.clone())
+                .diversity_metrics()
+        );
+        assert!(!Termination::<f64>::DiversityCollapse { threshold: 0.1 }.uses_diversity());
+        assert_eq!(None, low.check(&progress));
+        progress.update(
+            &GenerationStats {
+                diversity: Some(Diversity {
+                    unique: Some(1),
+                    ..Default::default()
+                }),
+                ..stats(4, 50, &[Some(7.0)])
+            },
+            Some(7.0),
+        );
+        assert_eq!(Some(low.clone()), low.check(&progress));
+    }
+
+    #[test]
+    fn test_lexicographic_progress() {
+        let objective = Objective::Minimize;
+        let stats =
+            |fitnesses: &[Option<(u32, f64)>]| GenerationStats::new(1, fitnesses, objective);
+        let mut progress = RunProgress::new(&stats(&[Some((0, 5.0))]), Some((0, 5.0)), objective);
+        let stop = Termination::Stagnation(1).or(Termination::TargetFitness((0, 1.0)));
+
+        // Same first element, lower cost: still improving.
+        progress.update(&stats(&[Some((0, 3.0))]), Some((0, 3.0)));
+        assert_eq!(0, progress.stagnant_generations);
+        assert_eq!(None, stop.check(&progress));
+
+        progress.update(&stats(&[Some((0, 1.0))]), Some((0, 1.0)));
+        assert_eq!(
+            Some(Termination::TargetFitness((0, 1.0))),
+            stop.check(&progress)
+        );
+
+        progress.update(&stats(&[Some((1, 0.0))]), Some((1, 0.0)));
+        assert_eq!(Some(Termination::Stagnation(1)), stop.check(&progress));
+        assert_eq!(Some((0, 1.0)), progress.best_fitness);
+        assert_eq!(
+            None,
+            Termination::TargetFitness(0.5).to_fitness::<(u32, f64)>()
+        );
+    }
+}
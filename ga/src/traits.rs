@@ -1,17 +1,131 @@
-use crate::population::MutationConfig;
+use std::{cmp::Ordering, fmt::Debug};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::population::{CrossoverConfig, MutationConfig};
 
 pub trait Crossover {
-    fn crossover(&self, other: &Self, seed: [u8; 32]) -> Self;
+    fn crossover(&self, other: &Self, config: &CrossoverConfig, seed: [u8; 32]) -> Self;
+    /// False if `crossover` can't combine `self` with `other`, such as
+    /// permutations of different lengths. `Population` reports these pairs
+    /// through `Observer::on_incompatible_crossover`.
+    fn can_cross(&self, _other: &Self) -> bool {
+        true
+    }
 }
 
-pub trait Fitness {
-    fn calculate_fitness(&mut self, seed: [u8; 32]) -> Option<f64>;
+/// How different two genomes are, for niching. Identical genomes are at 0.
+pub trait Distance {
+    fn distance(&self, other: &Self) -> f64;
+}
+
+/// Genomes made of genes at fixed positions, for gene-wise diversity.
+pub trait Genes {
+    type Gene: PartialEq;
+    fn genes(&self) -> &[Self::Gene];
+}
+
+pub trait Fitness: FitnessRetrieve {
+    fn calculate_fitness(&mut self, seed: [u8; 32]) -> Option<Self::Value>;
 }
 
 pub trait FitnessRetrieve {
-    fn get_fitness(&self) -> Option<f64>;
+    /// The fitness type; use `f64` unless the problem needs something else.
+    type Value: FitnessValue;
+    fn get_fitness(&self) -> Option<Self::Value>;
+    /// Replaces the stored fitness, for example with the mean of several noisy
+    /// samples or when resuming from a checkpoint.
+    fn set_fitness(&mut self, fitness: Option<Self::Value>);
+}
+
+/// A fitness type that can be ordered, and summarised as an `f64` for
+/// statistics, fitness-proportional selection and termination thresholds.
+///
+/// Tuples compare lexicographically and are summarised by their first
+/// element, so `(violations, cost)` with `Objective::Minimize` prefers
+/// feasible solutions, then cheaper ones.
+pub trait FitnessValue: Clone + Debug + Serialize + DeserializeOwned {
+    /// Natural order of the values, where greater is better under `Objective::Maximize`.
+    fn compare(&self, other: &Self) -> Ordering;
+    /// False for values that can't be ordered, such as NaN. These sort last.
+    fn is_valid(&self) -> bool {
+        true
+    }
+    /// A single number for the value, used by stats, niching,
+    /// fitness-proportional selection and `Termination::DiversityCollapse`.
+    /// Tuples give their first component.
+    fn to_f64(&self) -> f64;
+    /// False when `to_f64` keeps only part of the value, as for tuples.
+    fn is_scalar() -> bool {
+        true
+    }
+    /// Builds a value from an `f64` summary, such as the mean of noisy samples.
+    /// Types that can't be built this way return `None`.
+    fn from_f64(_value: f64) -> Option<Self> {
+        None
+    }
+}
+
+macro_rules! float_fitness_value {
+    ($($t:ty),*) => {$(
+        impl FitnessValue for $t {
+            fn compare(&self, other: &Self) -> Ordering {
+                self.partial_cmp(other).unwrap_or(Ordering::Equal)
+            }
+            fn is_valid(&self) -> bool {
+                !self.is_nan()
+            }
+            fn to_f64(&self) -> f64 {
+                *self as f64
+            }
+            fn from_f64(value: f64) -> Option<Self> {
+                Some(value as $t)
+            }
+        }
+    )*};
+}
+
+macro_rules! integer_fitness_value {
+    ($($t:ty),*) => {$(
+        impl FitnessValue for $t {
+            fn compare(&self, other: &Self) -> Ordering {
+                self.cmp(other)
+            }
+            fn to_f64(&self) -> f64 {
+                *self as f64
+            }
+            fn from_f64(value: f64) -> Option<Self> {
+                value.is_finite().then(|| value.round() as $t)
+            }
+        }
+    )*};
 }
 
+float_fitness_value!(f32, f64);
+integer_fitness_value!(i32, i64, u32, u64, usize);
+
+macro_rules! tuple_fitness_value {
+    ($($name:ident $index:tt),+) => {
+        impl<$($name: FitnessValue),+> FitnessValue for ($($name,)+) {
+            fn compare(&self, other: &Self) -> Ordering {
+                Ordering::Equal$(.then_with(|| self.$index.compare(&other.$index)))+
+            }
+            fn is_valid(&self) -> bool {
+                true $(&& self.$index.is_valid())+
+            }
+            fn to_f64(&self) -> f64 {
+                self.0.to_f64()
+            }
+            fn is_scalar() -> bool {
+                false
+            }
+        }
+    };
+}
+
+tuple_fitness_value!(A 0, B 1);
+tuple_fitness_value!(A 0, B 1, C 2);
+
 pub trait Mutate {
     fn mutate(&self, config: &MutationConfig, seed: [u8; 32]) -> Self;
 }
@@ -19,3 +133,8 @@ pub trait Mutate {
 pub trait Generate {
     fn generate(seed: [u8; 32]) -> Self;
 }
+
+/// Fitness for problems with several objectives, all of which are maximised.
+pub trait MultiObjectiveFitness {
+    fn calculate_objectives(&mut self, seed: [u8; 32]) -> Option<Vec<f64>>;
+}